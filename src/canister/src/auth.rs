@@ -0,0 +1,148 @@
+use crate::error::LightClientError;
+use candid::{CandidType, Principal};
+use ic_cdk::api::{caller, is_controller};
+use ic_lightclient_wire::ChainId;
+use serde::Deserialize;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+thread_local! {
+    static ROLES: RefCell<Roles> = RefCell::new(Roles::default());
+}
+
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct Roles {
+    admins: HashSet<Principal>,
+    agents: HashMap<ChainId, HashSet<Principal>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum Role {
+    Controller,
+    ConfigAdmin,
+    Agent(ChainId),
+    AnyAgent,
+    Canister,
+}
+
+pub struct AccessControl;
+
+impl AccessControl {
+    pub fn require_controller() -> Result<(), LightClientError> {
+        let caller = caller();
+        if is_controller(&caller) {
+            return Ok(());
+        }
+
+        Err(LightClientError::Unauthorized { caller, required: Role::Controller })
+    }
+
+    /// Controllers are implicitly config admins.
+    pub fn require_admin() -> Result<(), LightClientError> {
+        let caller = caller();
+        if is_controller(&caller) || ROLES.with_borrow(|roles| roles.admins.contains(&caller)) {
+            return Ok(());
+        }
+
+        Err(LightClientError::Unauthorized { caller, required: Role::ConfigAdmin })
+    }
+
+    pub fn require_agent(chain: ChainId) -> Result<(), LightClientError> {
+        let caller = caller();
+        let is_agent =
+            ROLES.with_borrow(|roles| roles.agents.get(&chain).is_some_and(|agents| agents.contains(&caller)));
+        if is_agent {
+            return Ok(());
+        }
+
+        Err(LightClientError::Unauthorized { caller, required: Role::Agent(chain) })
+    }
+
+    /// Upload sessions carry updates of chains which are only known once they are committed.
+    pub fn require_any_agent() -> Result<(), LightClientError> {
+        let caller = caller();
+        let is_agent = ROLES.with_borrow(|roles| roles.agents.values().any(|agents| agents.contains(&caller)));
+        if is_agent {
+            return Ok(());
+        }
+
+        Err(LightClientError::Unauthorized { caller, required: Role::AnyAgent })
+    }
+
+    /// Subscribers are notified at the canister's expense, so only canisters, which cost cycles to
+    /// create, may subscribe. Users, including the anonymous principal, are rejected.
+    pub fn require_canister() -> Result<(), LightClientError> {
+        let caller = caller();
+        if is_canister(&caller) {
+            return Ok(());
+        }
+
+        Err(LightClientError::Unauthorized { caller, required: Role::Canister })
+    }
+
+    pub fn roles() -> Roles {
+        ROLES.with_borrow(|roles| roles.clone())
+    }
+
+    pub fn restore(roles: Roles) {
+        ROLES.set(roles);
+    }
+
+    pub fn add_admin(principal: Principal) {
+        ROLES.with_borrow_mut(|roles| roles.admins.insert(principal));
+    }
+
+    pub fn remove_admin(principal: &Principal) {
+        ROLES.with_borrow_mut(|roles| roles.admins.remove(principal));
+    }
+
+    pub fn list_admins() -> Vec<Principal> {
+        ROLES.with_borrow(|roles| roles.admins.iter().cloned().collect())
+    }
+
+    pub fn add_agent(chain: ChainId, principal: Principal) {
+        ROLES.with_borrow_mut(|roles| roles.agents.entry(chain).or_default().insert(principal));
+    }
+
+    pub fn remove_agent(chain: ChainId, principal: &Principal) {
+        ROLES.with_borrow_mut(|roles| {
+            if let Some(agents) = roles.agents.get_mut(&chain) {
+                agents.remove(principal);
+            }
+        });
+    }
+
+    pub fn list_agents(chain: ChainId) -> Vec<Principal> {
+        ROLES.with_borrow(|roles| {
+            roles
+                .agents
+                .get(&chain)
+                .map(|agents| agents.iter().cloned().collect())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Canister ids are opaque principals, the other classes belong to users.
+fn is_canister(principal: &Principal) -> bool {
+    const OPAQUE_ID_CLASS: u8 = 0x01;
+
+    let bytes = principal.as_slice();
+    bytes.len() == 10 && bytes[9] == OPAQUE_ID_CLASS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_canister;
+    use candid::Principal;
+
+    #[test]
+    fn test_is_canister() {
+        assert!(is_canister(&Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap()));
+        assert!(!is_canister(&Principal::anonymous()));
+        assert!(!is_canister(&Principal::management_canister()));
+        assert!(!is_canister(&Principal::self_authenticating([7u8; 32])));
+    }
+}
@@ -1,34 +1,58 @@
 use crate::{
     chain::{Chain, GenericChainBlueprint, GenericChainFactory},
+    error::LightClientError,
     ethereum::{EthereumConfigManager, EthereumStateManager},
     outcalls::{OutcallsConfigManager, OutcallsStateManager},
+    storage::StableChain,
+};
+use anyhow::Result;
+use ic_lightclient_ethereum::helios::spec::{MainnetConsensusSpec, MinimalConsensusSpec};
+use ic_lightclient_wire::{
+    ethereum::{lightclient, outcalls},
+    ChainId,
 };
-use anyhow::{anyhow, Result};
-use ic_lightclient_ethereum::helios::spec::MainnetConsensusSpec;
-use ic_lightclient_wire::ethereum::{lightclient, outcalls};
 
 struct EthereumMainnetBlueprint;
 
 impl GenericChainBlueprint for EthereumMainnetBlueprint {
-    const CHAIN_UID: u16 = 1;
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_MAINNET;
     type ConfigManager = EthereumConfigManager;
     type StateManager = EthereumStateManager<MainnetConsensusSpec>;
     type Protocol = lightclient::EthereumWireProtocol<MainnetConsensusSpec>;
 }
 
+struct EthereumDevnetBlueprint;
+
+impl GenericChainBlueprint for EthereumDevnetBlueprint {
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_DEVNET;
+    type ConfigManager = EthereumConfigManager;
+    type StateManager = EthereumStateManager<MinimalConsensusSpec>;
+    type Protocol = lightclient::EthereumWireProtocol<MinimalConsensusSpec>;
+}
+
 struct EthereumHoleskyBlueprint;
 
 impl GenericChainBlueprint for EthereumHoleskyBlueprint {
-    const CHAIN_UID: u16 = 17000;
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_HOLESKY;
     type ConfigManager = OutcallsConfigManager;
     type StateManager = OutcallsStateManager;
     type Protocol = outcalls::OutcallsWireProtocol;
 }
 
-pub async fn build_chain_from_uid(uid: u16) -> Result<Box<dyn Chain>> {
+pub async fn build_chain_from_uid(uid: ChainId) -> Result<Box<dyn Chain>> {
     match uid {
         EthereumMainnetBlueprint::CHAIN_UID => GenericChainFactory::build::<EthereumMainnetBlueprint>().await,
         EthereumHoleskyBlueprint::CHAIN_UID => GenericChainFactory::build::<EthereumHoleskyBlueprint>().await,
-        _ => Err(anyhow!("Invalid uid")),
+        EthereumDevnetBlueprint::CHAIN_UID => GenericChainFactory::build::<EthereumDevnetBlueprint>().await,
+        _ => Err(LightClientError::UnknownChain(uid).into()),
+    }
+}
+
+pub fn restore_chain_from_uid(chain: StableChain) -> Result<Box<dyn Chain>> {
+    match chain.uid {
+        EthereumMainnetBlueprint::CHAIN_UID => GenericChainFactory::restore::<EthereumMainnetBlueprint>(chain),
+        EthereumHoleskyBlueprint::CHAIN_UID => GenericChainFactory::restore::<EthereumHoleskyBlueprint>(chain),
+        EthereumDevnetBlueprint::CHAIN_UID => GenericChainFactory::restore::<EthereumDevnetBlueprint>(chain),
+        uid => Err(LightClientError::UnknownChain(uid).into()),
     }
 }
@@ -1,28 +1,87 @@
-use crate::chain::{state::StateManager, ConfigManager};
+use crate::{
+    chain::{state::StateManager, ConfigManager, UpdateOutcome},
+    error::LightClientError,
+    outcalls::PendingBlockVotes,
+    storage::StableChain,
+    timers::ChainTask,
+    types::{
+        Account, BlockHeader, ChainUpdateReport, Commitment, Freshness, HeaderQuery, Log, LogQuery, RejectedUpdate,
+        StorageSlot, Transaction, TransactionProof,
+    },
+};
 use anyhow::Result;
 use async_trait::async_trait;
-use ic_lightclient_wire::{StatePayloadMarshaller, UpdatePayloadParser, WireProtocol};
-use std::marker::PhantomData;
+use ic_lightclient_wire::{
+    state_hash, ChainId, ChainStateDelta, ChainStateDigest, ChainWireVersions, StatePayloadMarshaller,
+    UpdatePayloadParser, VersionRange, WireProtocol,
+};
+use std::{marker::PhantomData, time::Duration};
 
 #[async_trait(?Send)]
 pub trait Chain {
     async fn init(&mut self);
     fn get_state(&self, marshaller: &mut StatePayloadMarshaller) -> Result<()>;
-    fn update_state(&mut self, updates: &UpdatePayloadParser) -> Result<()>;
-    fn get_latest_block_hash(&self) -> String;
-    fn get_base_gas_fee(&self) -> u128;
-    fn get_max_priority_fee(&self) -> u128;
+    fn get_state_digest(&self) -> ChainStateDigest;
+    /// Marshalled `ChainStateDelta` from `since_version` on, the full state when it isn't set.
+    fn get_state_delta(&self, since_version: Option<u64>) -> Result<Vec<u8>>;
+    fn get_wire_versions(&self) -> ChainWireVersions;
+    fn update_state(&mut self, updates: &UpdatePayloadParser) -> ChainUpdateReport;
+    fn get_latest_block_hash(&self) -> Result<String>;
+    fn get_latest_block(&self, commitment: Commitment) -> Result<BlockHeader>;
+    fn get_header(&self, query: &HeaderQuery) -> Result<BlockHeader>;
+    fn verify_ancestry(&self, proof: &[u8]) -> Result<BlockHeader>;
+    fn is_canonical(&self, block_number: u64, hash: &str) -> Result<bool>;
+    fn get_base_gas_fee(&self) -> Result<u128>;
+    fn get_next_base_gas_fee(&self) -> Result<u128>;
+    fn get_max_priority_fee(&self) -> Result<u128>;
+    fn get_pending_votes(&self) -> Result<Vec<PendingBlockVotes>>;
+    fn verify_account(&self, address: &str, block: Option<u64>) -> Result<Account>;
+    fn get_storage_at(&self, address: &str, slot: &str) -> Result<StorageSlot>;
+    fn get_logs(&self, query: &LogQuery) -> Result<Vec<Log>>;
+    fn verify_transaction(&self, tx_hash: &str, proof: TransactionProof) -> Result<Transaction>;
     fn get_config(&self) -> Result<Vec<u8>>;
+    fn get_stable_state(&self) -> Result<Vec<u8>>;
+    fn get_freshness(&self) -> Freshness;
+    fn get_tasks(&self) -> Vec<(ChainTask, Duration)>;
+    fn run_task(&mut self, task: ChainTask) -> Result<bool>;
+
+    /// Fails with `Stale` when `max_age` is set and the served data is older, or of unknown age.
+    fn ensure_fresh(&self, max_age: Option<u64>) -> Result<()> {
+        let Some(max_age) = max_age else { return Ok(()) };
+
+        let freshness = self.get_freshness();
+        if !freshness.is_within(max_age) {
+            return Err(LightClientError::Stale { age: freshness.age, max_age }.into());
+        }
+
+        Ok(())
+    }
+
+    /// Fails with `Conflict` when the updates were computed against a state other than the
+    /// current one. Updates sent without a base are applied as is.
+    fn ensure_base(&self, updates: &UpdatePayloadParser) -> Result<()> {
+        let digest = self.get_state_digest();
+        let Some((base_version, base_hash)) = updates.base(digest.chain) else { return Ok(()) };
+
+        if base_version != digest.version || base_hash != digest.hash.as_slice() {
+            return Err(
+                LightClientError::Conflict { chain: digest.chain, base_version, version: digest.version }.into()
+            );
+        }
+
+        Ok(())
+    }
 }
 
 pub trait GenericChainBlueprint {
-    const CHAIN_UID: u16;
+    const CHAIN_UID: ChainId;
     type ConfigManager: ConfigManager<Config = <Self::Protocol as WireProtocol>::Config> + 'static;
     type Protocol: WireProtocol;
     type StateManager: StateManager<
             Config = <Self::Protocol as WireProtocol>::Config,
             UpdatePayload = <Self::Protocol as WireProtocol>::UpdatePayload,
             StatePayload = <Self::Protocol as WireProtocol>::StatePayload,
+            StateDiff = <Self::Protocol as WireProtocol>::StateDiff,
         > + 'static;
 }
 
@@ -31,6 +90,10 @@ type ExtractConfig<B> = <<B as GenericChainBlueprint>::Protocol as WireProtocol>
 pub struct GenericChain<Blueprint: GenericChainBlueprint> {
     state: Blueprint::StateManager,
     config: ExtractConfig<Blueprint>,
+    last_updated: Option<u64>,
+    /// Incremented whenever the served state changes, along with the hash of the new state.
+    version: u64,
+    state_hash: Vec<u8>,
     blueprint: PhantomData<Blueprint>,
 }
 
@@ -39,7 +102,63 @@ impl<Blueprint: GenericChainBlueprint> GenericChain<Blueprint> {
         let config = Blueprint::ConfigManager::process(config).await?;
         let state = Blueprint::StateManager::new(config.clone());
 
-        Ok(Self { state, config, blueprint: PhantomData })
+        let mut chain =
+            Self { state, config, last_updated: None, version: 0, state_hash: vec![], blueprint: PhantomData };
+        chain.set_version(0);
+        Ok(chain)
+    }
+
+    /// Versions keep increasing across upgrades, so agents holding an older state refetch it.
+    pub fn restore(chain: StableChain) -> Result<Self> {
+        let config: ExtractConfig<Blueprint> = serde_json::from_slice(chain.config.as_slice())?;
+        let state = serde_json::from_slice(chain.state.as_slice())?;
+        let state = Blueprint::StateManager::restore(config.clone(), state);
+
+        let mut restored = Self {
+            state,
+            config,
+            last_updated: chain.last_updated,
+            version: 0,
+            state_hash: vec![],
+            blueprint: PhantomData,
+        };
+        restored.set_version(chain.version.map_or(0, |version| version + 1));
+        Ok(restored)
+    }
+
+    fn set_version(&mut self, version: u64) {
+        self.version = version;
+        self.state.record_version(version);
+        self.state_hash = self.compute_state_hash();
+    }
+
+    fn compute_state_hash(&self) -> Vec<u8> {
+        self.state
+            .get_state()
+            .and_then(|state| state_hash::<Blueprint::Protocol>(&state))
+            .unwrap_or_default()
+    }
+
+    fn report(&self, applied: u64, pending: u64, rejected: Vec<RejectedUpdate>) -> ChainUpdateReport {
+        ChainUpdateReport {
+            chain: Blueprint::CHAIN_UID,
+            applied,
+            pending,
+            rejected,
+            optimistic_slot: self.state.get_optimistic_slot(),
+            finalized_slot: self.state.get_finalized_slot(),
+        }
+    }
+
+    /// Bumps the version only when the served state changed, so updates which left it as is don't
+    /// conflict with updates based on it.
+    fn bump_version(&mut self) {
+        let hash = self.compute_state_hash();
+        if hash != self.state_hash {
+            self.version += 1;
+            self.state.record_version(self.version);
+            self.state_hash = hash;
+        }
     }
 }
 
@@ -53,37 +172,158 @@ impl<Blueprint: GenericChainBlueprint> Chain for GenericChain<Blueprint> {
         Ok(())
     }
 
+    fn get_state_digest(&self) -> ChainStateDigest {
+        ChainStateDigest { chain: Blueprint::CHAIN_UID, version: self.version, hash: self.state_hash.clone() }
+    }
+
+    fn get_wire_versions(&self) -> ChainWireVersions {
+        ChainWireVersions { chain: Blueprint::CHAIN_UID, payload: VersionRange::payload::<Blueprint::Protocol>() }
+    }
+
+    fn get_state_delta(&self, since_version: Option<u64>) -> Result<Vec<u8>> {
+        let (version, hash) = (self.version, self.state_hash.clone());
+        let diff = match since_version {
+            Some(since_version) if since_version == version => {
+                return ChainStateDelta::unchanged::<Blueprint::Protocol>(version, hash).build();
+            }
+            Some(since_version) if since_version < version => self.state.get_state_diff(since_version)?,
+            _ => None,
+        };
+
+        let delta = match diff {
+            Some(diff) => ChainStateDelta::diff::<Blueprint::Protocol>(version, hash, &diff)?,
+            None => ChainStateDelta::full::<Blueprint::Protocol>(version, hash, &self.state.get_state()?)?,
+        };
+        delta.build()
+    }
+
     // pub fn are_updates_valid(&self, _: ChainUpdates) -> bool {
     //     // Implement Ethereum-specific logic to validate updates
     //     true
     // }
 
-    fn update_state(&mut self, updates: &UpdatePayloadParser) -> Result<()> {
-        // TODO: Add timer checks
-        // TODO: Add check for conflicts
+    /// Updates are applied one by one and a rejected update doesn't prevent later ones from being
+    /// applied, since each of them is verified against the state left by its predecessors. Updates
+    /// based on an outdated state are all rejected with `Conflict`.
+    fn update_state(&mut self, updates: &UpdatePayloadParser) -> ChainUpdateReport {
+        if let Err(error) = self.ensure_base(updates) {
+            let error: LightClientError = error.into();
+            let rejected = (0..updates.update_count(Blueprint::CHAIN_UID))
+                .map(|index| RejectedUpdate { index: index as u64, error: error.clone() })
+                .collect();
 
-        let updates = updates.updates::<Blueprint::Protocol>(Blueprint::CHAIN_UID)?;
-        if updates.len() > 0 {
-            self.state.update_state(updates)?;
+            return self.report(0, 0, rejected);
         }
 
-        Ok(())
+        let mut applied = 0;
+        let mut pending = 0;
+        let mut rejected = vec![];
+
+        for (index, update) in updates
+            .updates::<Blueprint::Protocol>(Blueprint::CHAIN_UID)
+            .into_iter()
+            .enumerate()
+        {
+            let result = update
+                .map_err(|e| LightClientError::InvalidPayload(e.to_string()).into())
+                .and_then(|update| self.state.update_state(update));
+
+            match result {
+                Ok(UpdateOutcome::Applied) => applied += 1,
+                Ok(UpdateOutcome::Pending) => pending += 1,
+                Err(error) => rejected.push(RejectedUpdate { index: index as u64, error: error.into() }),
+            }
+        }
+
+        if applied > 0 {
+            self.last_updated = Some(ic_cdk::api::time());
+            self.bump_version();
+        }
+
+        self.report(applied, pending, rejected)
     }
 
-    fn get_latest_block_hash(&self) -> String {
+    fn get_latest_block_hash(&self) -> Result<String> {
         self.state.get_latest_block_hash()
     }
 
-    fn get_base_gas_fee(&self) -> u128 {
+    fn get_latest_block(&self, commitment: Commitment) -> Result<BlockHeader> {
+        self.state.get_latest_block(commitment)
+    }
+
+    fn get_header(&self, query: &HeaderQuery) -> Result<BlockHeader> {
+        self.state.get_header(query)
+    }
+
+    fn verify_ancestry(&self, proof: &[u8]) -> Result<BlockHeader> {
+        self.state.verify_ancestry(proof)
+    }
+
+    fn is_canonical(&self, block_number: u64, hash: &str) -> Result<bool> {
+        self.state.is_canonical(block_number, hash)
+    }
+
+    fn get_base_gas_fee(&self) -> Result<u128> {
         self.state.get_base_gas_fee()
     }
 
-    fn get_max_priority_fee(&self) -> u128 {
+    fn get_next_base_gas_fee(&self) -> Result<u128> {
+        self.state.get_next_base_gas_fee()
+    }
+
+    fn get_max_priority_fee(&self) -> Result<u128> {
         self.state.get_max_priority_fee()
     }
 
+    fn get_pending_votes(&self) -> Result<Vec<PendingBlockVotes>> {
+        self.state.get_pending_votes()
+    }
+
+    fn verify_account(&self, address: &str, block: Option<u64>) -> Result<Account> {
+        self.state.verify_account(address, block)
+    }
+
+    fn get_storage_at(&self, address: &str, slot: &str) -> Result<StorageSlot> {
+        self.state.get_storage_at(address, slot)
+    }
+
+    fn get_logs(&self, query: &LogQuery) -> Result<Vec<Log>> {
+        self.state.get_logs(query)
+    }
+
+    fn verify_transaction(&self, tx_hash: &str, proof: TransactionProof) -> Result<Transaction> {
+        self.state.verify_transaction(tx_hash, proof)
+    }
+
     fn get_config(&self) -> Result<Vec<u8>> {
         let serialized = serde_json::to_vec(&self.config)?;
         Ok(serialized)
     }
+
+    fn get_stable_state(&self) -> Result<Vec<u8>> {
+        let serialized = serde_json::to_vec(&self.state.stable_state()?)?;
+        Ok(serialized)
+    }
+
+    fn get_tasks(&self) -> Vec<(ChainTask, Duration)> {
+        self.state.get_tasks()
+    }
+
+    fn run_task(&mut self, task: ChainTask) -> Result<bool> {
+        let changed = self.state.run_task(task)?;
+        if changed {
+            self.bump_version();
+        }
+
+        Ok(changed)
+    }
+
+    fn get_freshness(&self) -> Freshness {
+        Freshness::new(
+            self.last_updated,
+            self.state.get_chain_timestamp(),
+            self.state.get_freshness_sla(),
+            ic_cdk::api::time(),
+        )
+    }
 }
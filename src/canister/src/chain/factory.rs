@@ -1,6 +1,7 @@
 use crate::{
     chain::{Chain, GenericChain, GenericChainBlueprint},
     config::ConfigManager,
+    storage::StableChain,
 };
 use anyhow::{anyhow, Result};
 
@@ -12,4 +13,9 @@ impl GenericChainFactory {
         let chain = GenericChain::<B>::new(config).await?;
         Ok(Box::new(chain))
     }
+
+    pub fn restore<B: GenericChainBlueprint + 'static>(chain: StableChain) -> Result<Box<dyn Chain>> {
+        let chain = GenericChain::<B>::restore(chain)?;
+        Ok(Box::new(chain))
+    }
 }
@@ -6,4 +6,4 @@ mod state;
 pub use chain::{Chain, GenericChain, GenericChainBlueprint};
 pub use config::ConfigManager;
 pub use factory::GenericChainFactory;
-pub use state::StateManager;
+pub use state::{StateManager, UpdateOutcome};
@@ -1,16 +1,103 @@
+use crate::{
+    error::LightClientError,
+    outcalls::PendingBlockVotes,
+    timers::ChainTask,
+    types::{Account, BlockHeader, Commitment, HeaderQuery, Log, LogQuery, StorageSlot, Transaction, TransactionProof},
+};
 use anyhow::Result;
 use serde::{de::DeserializeOwned, Serialize};
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Duration};
+
+/// Result of an accepted update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Applied,
+    /// Buffered until enough agents submit the same update, the served state is unchanged.
+    Pending,
+}
 
 pub trait StateManager {
     type Config: Debug;
     type StatePayload: Serialize + Debug;
     type UpdatePayload: DeserializeOwned + Debug;
+    type StateDiff: Serialize + Debug;
+    type StableState: Serialize + DeserializeOwned;
 
     fn new(config: Self::Config) -> Self;
+    fn restore(config: Self::Config, state: Self::StableState) -> Self;
+    fn stable_state(&self) -> Result<Self::StableState>;
     fn get_state(&self) -> Result<Self::StatePayload>;
-    fn update_state(&mut self, updates: Vec<Self::UpdatePayload>) -> Result<()>;
-    fn get_latest_block_hash(&self) -> String;
-    fn get_base_gas_fee(&self) -> u128;
-    fn get_max_priority_fee(&self) -> u128;
+    fn update_state(&mut self, update: Self::UpdatePayload) -> Result<UpdateOutcome>;
+    fn get_latest_block_hash(&self) -> Result<String>;
+    fn get_base_gas_fee(&self) -> Result<u128>;
+    fn get_max_priority_fee(&self) -> Result<u128>;
+    /// Chain reported timestamp in seconds of the served data.
+    fn get_chain_timestamp(&self) -> Option<u64>;
+    fn get_freshness_sla(&self) -> Option<u64>;
+
+    /// Called with the new version of the chain whenever its served state changed.
+    fn record_version(&mut self, _version: u64) {}
+
+    /// Fields changed since `since_version`, `None` when only the full state can be served.
+    fn get_state_diff(&self, _since_version: u64) -> Result<Option<Self::StateDiff>> {
+        Ok(None)
+    }
+
+    fn get_optimistic_slot(&self) -> Option<u64> {
+        None
+    }
+
+    fn get_finalized_slot(&self) -> Option<u64> {
+        None
+    }
+
+    /// Periodic tasks of the chain with their intervals.
+    fn get_tasks(&self) -> Vec<(ChainTask, Duration)> {
+        vec![]
+    }
+
+    /// Returns whether the task changed the served state.
+    fn run_task(&mut self, task: ChainTask) -> Result<bool> {
+        Err(LightClientError::Unsupported(format!("{:?}", task)).into())
+    }
+
+    fn get_next_base_gas_fee(&self) -> Result<u128> {
+        Err(LightClientError::Unsupported("verified headers".into()).into())
+    }
+
+    fn get_latest_block(&self, _commitment: Commitment) -> Result<BlockHeader> {
+        Err(LightClientError::Unsupported("verified headers".into()).into())
+    }
+
+    fn get_header(&self, _query: &HeaderQuery) -> Result<BlockHeader> {
+        Err(LightClientError::Unsupported("header history".into()).into())
+    }
+
+    fn verify_ancestry(&self, _proof: &[u8]) -> Result<BlockHeader> {
+        Err(LightClientError::Unsupported("ancestry proofs".into()).into())
+    }
+
+    fn is_canonical(&self, _block_number: u64, _hash: &str) -> Result<bool> {
+        Err(LightClientError::Unsupported("canonical chain lookups".into()).into())
+    }
+
+    fn verify_account(&self, _address: &str, _block: Option<u64>) -> Result<Account> {
+        Err(LightClientError::Unsupported("state proofs".into()).into())
+    }
+
+    fn get_storage_at(&self, _address: &str, _slot: &str) -> Result<StorageSlot> {
+        Err(LightClientError::Unsupported("state proofs".into()).into())
+    }
+
+    fn get_logs(&self, _query: &LogQuery) -> Result<Vec<Log>> {
+        Err(LightClientError::Unsupported("receipt proofs".into()).into())
+    }
+
+    fn verify_transaction(&self, _tx_hash: &str, _proof: TransactionProof) -> Result<Transaction> {
+        Err(LightClientError::Unsupported("transaction proofs".into()).into())
+    }
+
+    fn get_pending_votes(&self) -> Result<Vec<PendingBlockVotes>> {
+        Err(LightClientError::Unsupported("agent votes".into()).into())
+    }
 }
@@ -1,23 +1,32 @@
+use ic_lightclient_wire::ChainId;
 use std::{cell::RefCell, collections::HashMap};
 
 thread_local! {
-    static CONFIG: RefCell<HashMap<u16, String>> = RefCell::new(HashMap::new());
+    static CONFIG: RefCell<HashMap<ChainId, String>> = RefCell::new(HashMap::new());
 }
 
 pub struct ConfigManager;
 
 impl ConfigManager {
-    pub fn get(chain: u16) -> Option<String> {
+    pub fn get(chain: ChainId) -> Option<String> {
         CONFIG.with_borrow(|map| map.get(&chain).map(|v| v.to_string()))
     }
 
-    pub fn set(chain: u16, config: String) {
-        CONFIG.with_borrow_mut(|map: &mut HashMap<u16, String>| {
+    pub fn set(chain: ChainId, config: String) {
+        CONFIG.with_borrow_mut(|map: &mut HashMap<ChainId, String>| {
             map.insert(chain, config);
         });
     }
 
-    pub fn list() -> Vec<u16> {
-        CONFIG.with_borrow(|map| map.keys().map(|k| k.clone()).collect())
+    pub fn list() -> Vec<ChainId> {
+        CONFIG.with_borrow(|map| map.keys().copied().collect())
+    }
+
+    pub fn entries() -> Vec<(ChainId, String)> {
+        CONFIG.with_borrow(|map| map.iter().map(|(k, v)| (*k, v.clone())).collect())
+    }
+
+    pub fn restore(entries: Vec<(ChainId, String)>) {
+        CONFIG.set(entries.into_iter().collect());
     }
 }
@@ -0,0 +1,104 @@
+use crate::auth::Role;
+use candid::{CandidType, Principal};
+use ic_lightclient_ethereum::{execution::ProofError, helios::errors::ConsensusError as HeliosConsensusError};
+use ic_lightclient_wire::ChainId;
+use serde::Deserialize;
+use thiserror::Error;
+
+/// Candid mirror of the light client `ConsensusError`, with hashes rendered as hex strings.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ConsensusError {
+    InsufficientParticipation,
+    InvalidTimestamp,
+    InvalidPeriod,
+    NotRelevant,
+    InvalidFinalityProof,
+    InvalidNextSyncCommitteeProof,
+    InvalidCurrentSyncCommitteeProof,
+    InvalidExecutionPayloadProof,
+    InvalidAncestryProof,
+    InvalidSignature,
+    InvalidHeaderHash { found: String, expected: String },
+    PayloadNotFound(u64),
+    CheckpointTooOld,
+    IncorrectRpcNetwork,
+    UnknownFork(u64),
+}
+
+impl From<&HeliosConsensusError> for ConsensusError {
+    fn from(err: &HeliosConsensusError) -> Self {
+        match err {
+            HeliosConsensusError::InsufficientParticipation => Self::InsufficientParticipation,
+            HeliosConsensusError::InvalidTimestamp => Self::InvalidTimestamp,
+            HeliosConsensusError::InvalidPeriod => Self::InvalidPeriod,
+            HeliosConsensusError::NotRelevant => Self::NotRelevant,
+            HeliosConsensusError::InvalidFinalityProof => Self::InvalidFinalityProof,
+            HeliosConsensusError::InvalidNextSyncCommitteeProof => Self::InvalidNextSyncCommitteeProof,
+            HeliosConsensusError::InvalidCurrentSyncCommitteeProof => Self::InvalidCurrentSyncCommitteeProof,
+            HeliosConsensusError::InvalidExecutionPayloadProof => Self::InvalidExecutionPayloadProof,
+            HeliosConsensusError::InvalidAncestryProof => Self::InvalidAncestryProof,
+            HeliosConsensusError::InvalidSignature => Self::InvalidSignature,
+            HeliosConsensusError::InvalidHeaderHash(found, expected) => {
+                Self::InvalidHeaderHash { found: found.to_string(), expected: expected.to_string() }
+            }
+            HeliosConsensusError::PayloadNotFound(slot) => Self::PayloadNotFound(*slot),
+            HeliosConsensusError::CheckpointTooOld => Self::CheckpointTooOld,
+            HeliosConsensusError::IncorrectRpcNetwork => Self::IncorrectRpcNetwork,
+            HeliosConsensusError::UnknownFork(epoch) => Self::UnknownFork(*epoch),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Error)]
+pub enum LightClientError {
+    #[error("global state not initialized")]
+    NotInitialized,
+    #[error("unknown chain: {0}")]
+    UnknownChain(ChainId),
+    #[error("light client not bootstrapped")]
+    NotBootstrapped,
+    #[error("invalid payload: {0}")]
+    InvalidPayload(String),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("invalid proof: {0}")]
+    InvalidProof(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("verification failed: {0:?}")]
+    VerificationFailed(ConsensusError),
+    #[error("caller {caller} is not authorized, requires {required:?}")]
+    Unauthorized { caller: Principal, required: Role },
+    #[error("served data is stale, age: {age:?}s, max age: {max_age}s")]
+    Stale { age: Option<u64>, max_age: u64 },
+    #[error("chain {chain} state changed since version {base_version}, now at {version}; refetch the state")]
+    Conflict { chain: ChainId, base_version: u64, version: u64 },
+    #[error("unknown subscription: {0}")]
+    UnknownSubscription(u64),
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
+    #[error("unsupported by chain: {0}")]
+    Unsupported(String),
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+/// Internal code reports errors through `anyhow`; typed errors raised along the way are recovered
+/// here and everything else is reported as internal.
+impl From<anyhow::Error> for LightClientError {
+    fn from(err: anyhow::Error) -> Self {
+        if let Some(err) = err.downcast_ref::<LightClientError>() {
+            return err.clone();
+        }
+
+        if let Some(err) = err.downcast_ref::<HeliosConsensusError>() {
+            return Self::VerificationFailed(err.into());
+        }
+
+        if let Some(err) = err.downcast_ref::<ProofError>() {
+            return Self::InvalidProof(err.to_string());
+        }
+
+        Self::Internal(format!("{:#}", err))
+    }
+}
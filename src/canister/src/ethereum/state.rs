@@ -1,19 +1,394 @@
-use crate::chain::StateManager;
-use anyhow::Result;
+use crate::{
+    chain::{StateManager, UpdateOutcome},
+    error::LightClientError,
+    timers::ChainTask,
+    types::{
+        Account, BlockHeader, Commitment, HeaderQuery, Log, LogQuery, StorageSlot, Transaction, TransactionProof,
+        TransactionType,
+    },
+};
+use alloy_consensus::{Transaction as _, TxEnvelope};
+use alloy_primitives::{keccak256, Address, Log as EthereumLog, B256, U256};
+use anyhow::{anyhow, Result};
+use candid::Nat;
 use ic_lightclient_ethereum::{
-    config::EthereumConfigPopulated, helios::spec::ConsensusSpec, EthereumLightClientConsensus,
+    config::EthereumConfigPopulated,
+    execution::{AccountProof, HeaderChain, ReceiptProof, TransactionProof as EthereumTransactionProof},
+    fees::next_base_fee_per_gas,
+    helios::{
+        proof::AncestryProof,
+        spec::ConsensusSpec,
+        types::{Bootstrap, GenericUpdate, LightClientHeader, LightClientStore},
+    },
+    payload::{LightClientState, LightClientStoreDiff},
+    EthereumLightClientConsensus,
 };
+use ic_lightclient_utils::CircularQueue;
 use ic_lightclient_wire::ethereum::lightclient::{Block, LightClientStatePayload, LightClientUpdatePayload};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use ssz::Decode;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+const MAX_PROVEN_LOGS: usize = 1024;
+const MAX_HEADER_CHAIN_LENGTH: usize = 1024;
+
+fn to_nat(value: U256) -> Result<Nat> {
+    Nat::parse(value.to_string().as_bytes()).map_err(|e| anyhow!("{}", e))
+}
+
+/// Account state proven against the execution state root of `block_number`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProvenAccount {
+    block_number: u64,
+    state_root: B256,
+    nonce: u64,
+    balance: U256,
+    storage_hash: B256,
+    code_hash: B256,
+    storage: BTreeMap<B256, U256>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProvenLog {
+    block_number: u64,
+    block_hash: B256,
+    transaction_index: u64,
+    log_index: u64,
+    log: EthereumLog,
+    /// Whether the block is known to be canonical, logs of optimistic blocks which get reorged
+    /// out are dropped.
+    #[serde(default)]
+    finalized: bool,
+}
+
+/// Verified header of the history, finalized once it was the finalized header of the consensus.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoricalHeader {
+    header: LightClientHeader,
+    finalized: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "S: ConsensusSpec + Serialize + DeserializeOwned")]
+pub struct EthereumStableState<S: ConsensusSpec> {
+    consensus: LightClientState<S>,
+    block: Block,
+    #[serde(default)]
+    accounts: BTreeMap<Address, ProvenAccount>,
+    #[serde(default)]
+    logs: VecDeque<ProvenLog>,
+    #[serde(default)]
+    history: Vec<HistoricalHeader>,
+    #[serde(default)]
+    canonical_hashes: BTreeMap<u64, B256>,
+}
+
+/// Version at which each field of the store last changed.
+#[derive(Default)]
+struct StoreVersions {
+    active_since: u64,
+    optimistic_header: u64,
+    finalized_header: u64,
+    current_sync_committee: u64,
+    next_sync_committee: u64,
+    best_valid_update: u64,
+    participants: u64,
+}
 
 pub struct EthereumStateManager<S: ConsensusSpec> {
     consensus: EthereumLightClientConsensus<S>,
     block: Block,
+    accounts: BTreeMap<Address, ProvenAccount>,
+    /// Most recent proven logs of the watched filters, oldest first.
+    logs: VecDeque<ProvenLog>,
+    /// Recently verified headers, oldest first.
+    history: CircularQueue<HistoricalHeader>,
+    /// Finalized execution block hashes backfilled through `parent_hash` links, by block number.
+    canonical_hashes: BTreeMap<u64, B256>,
+    canonical_hashes_depth: usize,
+    /// Store at the latest version, diffs are served from it.
+    store_snapshot: Option<LightClientStore<S>>,
+    store_versions: StoreVersions,
 }
 
 impl<S: ConsensusSpec> EthereumStateManager<S> {
-    fn update_block(&mut self, block: Block) {
+    fn header(&self, commitment: Commitment) -> Result<&LightClientHeader> {
+        let header = match commitment {
+            Commitment::Optimistic => self.consensus.get_optimistic_header(),
+            Commitment::Finalized => self.consensus.get_finalized_header(),
+        };
+
+        Ok(header.ok_or(LightClientError::NotBootstrapped)?)
+    }
+
+    /// Agent reported blocks are only used for the fields which are not part of the execution
+    /// header, so they must refer to the verified optimistic header.
+    fn update_block(&mut self, block: Block) -> Result<()> {
+        let header = self.header(Commitment::Optimistic)?;
+        let block_hash: B256 = block
+            .block_hash
+            .parse()
+            .map_err(|_| LightClientError::InvalidPayload(format!("Invalid block hash {}.", block.block_hash)))?;
+
+        if block.block_num != header.execution.block_number as u128 || block_hash != header.execution.block_hash {
+            let message = format!("Block {} does not match the verified execution header.", block.block_num);
+            return Err(LightClientError::InvalidPayload(message).into());
+        }
+
         self.block = block;
+        Ok(())
+    }
+
+    /// Looks up the current headers first, then the history from the newest header on.
+    fn find_header(
+        &self,
+        matches: impl Fn(&LightClientHeader, Commitment) -> bool,
+    ) -> Option<(&LightClientHeader, Commitment)> {
+        let current = [Commitment::Finalized, Commitment::Optimistic]
+            .into_iter()
+            .filter_map(|commitment| self.header(commitment).ok().map(|header| (header, commitment)));
+        let history = (0..self.history.size())
+            .rev()
+            .filter_map(|i| self.history.at_index(i))
+            .map(|known| {
+                let commitment = if known.finalized { Commitment::Finalized } else { Commitment::Optimistic };
+                (&known.header, commitment)
+            });
+
+        current.chain(history).find(|(header, commitment)| matches(header, *commitment))
+    }
+
+    /// Execution proofs are verified against the verified header at the proven block, which may
+    /// be one of the history.
+    fn header_at(&self, block_number: u64) -> Result<&LightClientHeader> {
+        let header = self.find_header(|header, _| header.execution.block_number == block_number);
+
+        Ok(header
+            .map(|(header, _)| header)
+            .ok_or(LightClientError::InvalidPayload(format!(
+                "Block {} is not a verified execution header.",
+                block_number
+            )))?)
+    }
+
+    /// Records the finalized and optimistic headers of the consensus, each header once.
+    fn record_headers(&mut self) {
+        if self.history.capacity() == 0 {
+            return;
+        }
+
+        for commitment in [Commitment::Finalized, Commitment::Optimistic] {
+            let Ok(header) = self.header(commitment).cloned() else {
+                continue;
+            };
+            let finalized = commitment == Commitment::Finalized;
+
+            let known = (0..self.history.size()).find(|&i| {
+                self.history.at_index(i).is_some_and(|known| {
+                    known.header.beacon.slot == header.beacon.slot
+                        && known.header.execution.block_hash == header.execution.block_hash
+                })
+            });
+
+            match known.and_then(|i| self.history.at_index_mut(i)) {
+                Some(known) => known.finalized |= finalized,
+                None => self.history.queue(HistoricalHeader { header, finalized }),
+            }
+        }
+    }
+
+    /// The run has to end at a finalized header or at an already backfilled hash, so the chain
+    /// can be extended backwards over several payloads. The newest hashes are kept.
+    fn apply_header_chain(&mut self, chain: HeaderChain) -> Result<()> {
+        if chain.headers.len() > MAX_HEADER_CHAIN_LENGTH {
+            let message = format!("Header chain is longer than {} headers.", MAX_HEADER_CHAIN_LENGTH);
+            return Err(LightClientError::InvalidPayload(message).into());
+        }
+
+        let last = chain
+            .headers
+            .last()
+            .ok_or(LightClientError::InvalidPayload("Empty header chain.".into()))?;
+        let anchor = keccak256(last);
+        let is_known = self.canonical_hashes.values().any(|hash| *hash == anchor)
+            || self
+                .find_header(|header, commitment| {
+                    commitment == Commitment::Finalized && header.execution.block_hash == anchor
+                })
+                .is_some();
+        if !is_known {
+            let message = format!("Header chain does not end at a finalized block, got {}.", anchor);
+            return Err(LightClientError::InvalidPayload(message).into());
+        }
+
+        self.canonical_hashes.extend(chain.verify(anchor)?);
+        while self.canonical_hashes.len() > self.canonical_hashes_depth {
+            self.canonical_hashes.pop_first();
+        }
+        self.settle_logs();
+
+        Ok(())
+    }
+
+    /// Only accounts of the config are kept to bound the stored state.
+    fn apply_account_proof(&mut self, proof: AccountProof) -> Result<()> {
+        let is_watched = self
+            .consensus
+            .get_config()
+            .watched_accounts
+            .iter()
+            .any(|account| account.address == proof.address);
+        if !is_watched {
+            return Err(LightClientError::InvalidPayload(format!("Account {} is not watched.", proof.address)).into());
+        }
+
+        let state_root = self.header_at(proof.block_number)?.execution.state_root;
+        proof.verify(state_root)?;
+
+        let account = ProvenAccount {
+            block_number: proof.block_number,
+            state_root,
+            nonce: proof.nonce,
+            balance: proof.balance,
+            storage_hash: proof.storage_hash,
+            code_hash: proof.code_hash,
+            storage: proof
+                .storage_proof
+                .iter()
+                .map(|storage| (storage.slot, storage.value))
+                .collect(),
+        };
+        self.accounts.insert(proof.address, account);
+
+        Ok(())
+    }
+
+    /// Keeps the logs of the receipt which match the watched filters, with the commitment of the
+    /// header they were proven against. Receipts proven again, once for the optimistic and once for
+    /// the finalized block, are only kept once.
+    fn apply_receipt_proof(&mut self, proof: ReceiptProof) -> Result<()> {
+        let (header, commitment) = self
+            .find_header(|header, _| header.execution.block_number == proof.block_number)
+            .ok_or(LightClientError::InvalidPayload(format!(
+                "Block {} is not a verified execution header.",
+                proof.block_number
+            )))?;
+        let (block_hash, receipts_root) = (header.execution.block_hash, header.execution.receipts_root);
+        let finalized = commitment == Commitment::Finalized;
+        let receipt = proof.verify(receipts_root)?;
+
+        let mut is_known = false;
+        for log in self.logs.iter_mut() {
+            if log.block_hash == block_hash && log.transaction_index == proof.index {
+                log.finalized |= finalized;
+                is_known = true;
+            }
+        }
+        if is_known {
+            return Ok(());
+        }
+
+        let filters = &self.consensus.get_config().watched_logs;
+        for (log_index, log) in receipt.logs().iter().enumerate() {
+            if !filters.iter().any(|filter| filter.matches(log)) {
+                continue;
+            }
+
+            if self.logs.len() >= MAX_PROVEN_LOGS {
+                self.logs.pop_front();
+            }
+
+            self.logs.push_back(ProvenLog {
+                block_number: proof.block_number,
+                block_hash,
+                transaction_index: proof.index,
+                log_index: log_index as u64,
+                log: log.clone(),
+                finalized,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Settles the optimistic logs at or below the finalized block. A log is finalized once its
+    /// block hash is the finalized one at its height, and dropped once another block is. Logs of
+    /// blocks without a known finalized hash stay optimistic until the header chain reaches them.
+    fn settle_logs(&mut self) {
+        let Ok(finalized_number) = self.header(Commitment::Finalized).map(|header| header.execution.block_number)
+        else {
+            return;
+        };
+
+        let canonical_hash = |block_number: u64| {
+            self.canonical_hashes.get(&block_number).copied().or_else(|| {
+                self.find_header(|header, commitment| {
+                    commitment == Commitment::Finalized && header.execution.block_number == block_number
+                })
+                .map(|(header, _)| header.execution.block_hash)
+            })
+        };
+        let settled: Vec<Option<bool>> = self
+            .logs
+            .iter()
+            .map(|log| {
+                if log.finalized || log.block_number > finalized_number {
+                    return Some(log.finalized);
+                }
+
+                match canonical_hash(log.block_number) {
+                    Some(hash) if hash == log.block_hash => Some(true),
+                    Some(_) => None,
+                    None => Some(false),
+                }
+            })
+            .collect();
+
+        let mut settled = settled.into_iter();
+        self.logs.retain_mut(|log| match settled.next().flatten() {
+            Some(finalized) => {
+                log.finalized = finalized;
+                true
+            }
+            None => false,
+        });
+    }
+
+    fn proven_account(&self, address: &str) -> Result<(Address, &ProvenAccount)> {
+        let address: Address = address
+            .parse()
+            .map_err(|_| LightClientError::InvalidArgument(format!("Invalid address {}.", address)))?;
+        let account = self
+            .accounts
+            .get(&address)
+            .ok_or(LightClientError::NotFound(format!("No proven state for account {}.", address)))?;
+
+        Ok((address, account))
+    }
+
+    fn apply_bootstrap(&mut self, bootstrap: &Bootstrap<S>) -> Result<()> {
+        if self.consensus.is_bootstrapped() {
+            return Err(LightClientError::InvalidPayload("Light client is already bootstrapped.".into()).into());
+        }
+
+        self.consensus.bootstrap(bootstrap)?;
+        self.record_headers();
+        Ok(())
+    }
+
+    fn apply_update(&mut self, update: &GenericUpdate<S>) -> Result<()> {
+        if !self.consensus.is_bootstrapped() {
+            return Err(LightClientError::NotBootstrapped.into());
+        }
+
+        let current_time = ic_cdk::api::time();
+        self.consensus.update(update, current_time)?;
+        self.record_headers();
+        self.settle_logs();
+        Ok(())
     }
 }
 
@@ -21,45 +396,400 @@ impl<S: ConsensusSpec + Serialize + DeserializeOwned> StateManager for EthereumS
     type Config = EthereumConfigPopulated;
     type StatePayload = LightClientStatePayload<S>;
     type UpdatePayload = LightClientUpdatePayload<S>;
+    type StateDiff = LightClientStoreDiff<S>;
+    type StableState = EthereumStableState<S>;
 
     fn new(config: Self::Config) -> Self {
+        let history = CircularQueue::new(config.header_history_depth);
+        let canonical_hashes_depth = config.canonical_hashes_depth;
         let consensus = EthereumLightClientConsensus::new(config);
-        Self { consensus, block: Block::default() }
+        Self {
+            consensus,
+            block: Block::default(),
+            accounts: BTreeMap::new(),
+            logs: VecDeque::new(),
+            history,
+            canonical_hashes: BTreeMap::new(),
+            canonical_hashes_depth,
+            store_snapshot: None,
+            store_versions: StoreVersions::default(),
+        }
+    }
+
+    fn restore(config: Self::Config, state: Self::StableState) -> Self {
+        // The depth may have changed with the config, the newest headers are kept.
+        let mut history = CircularQueue::new(config.header_history_depth);
+        if history.capacity() > 0 {
+            state.history.into_iter().for_each(|header| history.queue(header));
+        }
+
+        let mut canonical_hashes = state.canonical_hashes;
+        while canonical_hashes.len() > config.canonical_hashes_depth {
+            canonical_hashes.pop_first();
+        }
+
+        let canonical_hashes_depth = config.canonical_hashes_depth;
+        let mut consensus = EthereumLightClientConsensus::new(config);
+        consensus.load_state(state.consensus);
+        Self {
+            consensus,
+            block: state.block,
+            accounts: state.accounts,
+            logs: state.logs,
+            history,
+            canonical_hashes,
+            canonical_hashes_depth,
+            store_snapshot: None,
+            store_versions: StoreVersions::default(),
+        }
+    }
+
+    fn stable_state(&self) -> Result<Self::StableState> {
+        let consensus = self.consensus.get_state()?;
+        Ok(EthereumStableState {
+            consensus,
+            block: self.block.clone(),
+            accounts: self.accounts.clone(),
+            logs: self.logs.clone(),
+            history: self.history.iter().cloned().collect(),
+            canonical_hashes: self.canonical_hashes.clone(),
+        })
     }
 
     fn get_state(&self) -> Result<Self::StatePayload> {
         self.consensus.get_state()
     }
 
-    fn update_state(&mut self, updates: Vec<Self::UpdatePayload>) -> Result<()> {
-        for update in updates {
-            match update {
-                LightClientUpdatePayload::Block(block) => {
-                    self.update_block(block.clone());
-                }
+    fn update_state(&mut self, update: Self::UpdatePayload) -> Result<UpdateOutcome> {
+        match update {
+            LightClientUpdatePayload::Block(block) => self.update_block(block),
+            LightClientUpdatePayload::Bootstrap(bootstrap) => self.apply_bootstrap(&bootstrap),
+            LightClientUpdatePayload::Update(update) => self.apply_update(&(&update).into()),
+            LightClientUpdatePayload::FinalityUpdate(update) => self.apply_update(&(&update).into()),
+            LightClientUpdatePayload::OptimisticUpdate(update) => self.apply_update(&(&update).into()),
+            LightClientUpdatePayload::GenericUpdate(update) => self.apply_update(&update),
+            LightClientUpdatePayload::AccountProof(proof) => self.apply_account_proof(proof),
+            LightClientUpdatePayload::ReceiptProof(proof) => self.apply_receipt_proof(proof),
+            LightClientUpdatePayload::HeaderChain(chain) => self.apply_header_chain(chain),
+        }?;
 
-                LightClientUpdatePayload::Bootstrap(bootstrap) => {
-                    self.consensus.bootstrap(&bootstrap)?;
-                }
+        Ok(UpdateOutcome::Applied)
+    }
 
-                LightClientUpdatePayload::Update(update) => {
-                    self.consensus.patch(update);
+    fn record_version(&mut self, version: u64) {
+        if !self.consensus.is_bootstrapped() {
+            self.store_snapshot = None;
+            return;
+        }
+
+        let store = self.consensus.get_store();
+        let versions = &mut self.store_versions;
+        match &self.store_snapshot {
+            None => {
+                *versions = StoreVersions {
+                    active_since: version,
+                    optimistic_header: version,
+                    finalized_header: version,
+                    current_sync_committee: version,
+                    next_sync_committee: version,
+                    best_valid_update: version,
+                    participants: version,
+                }
+            }
+            Some(snapshot) => {
+                if snapshot.optimistic_header != store.optimistic_header {
+                    versions.optimistic_header = version;
+                }
+                if snapshot.finalized_header != store.finalized_header {
+                    versions.finalized_header = version;
+                }
+                if snapshot.current_sync_committee != store.current_sync_committee {
+                    versions.current_sync_committee = version;
+                }
+                if snapshot.next_sync_committee != store.next_sync_committee {
+                    versions.next_sync_committee = version;
+                }
+                if snapshot.best_valid_update != store.best_valid_update {
+                    versions.best_valid_update = version;
+                }
+                if snapshot.previous_max_active_participants != store.previous_max_active_participants
+                    || snapshot.current_max_active_participants != store.current_max_active_participants
+                {
+                    versions.participants = version;
                 }
             }
         }
 
-        Ok(())
+        self.store_snapshot = Some(store.clone());
+    }
+
+    /// Diffs need the store the agent holds to be one of the versions since the light client got
+    /// bootstrapped, or restored.
+    fn get_state_diff(&self, since_version: u64) -> Result<Option<Self::StateDiff>> {
+        let Some(store) = &self.store_snapshot else { return Ok(None) };
+        let versions = &self.store_versions;
+        if since_version < versions.active_since {
+            return Ok(None);
+        }
+
+        let changed = |version: u64| version > since_version;
+        Ok(Some(LightClientStoreDiff {
+            optimistic_header: changed(versions.optimistic_header).then(|| store.optimistic_header.clone()),
+            finalized_header: changed(versions.finalized_header).then(|| store.finalized_header.clone()),
+            current_sync_committee: changed(versions.current_sync_committee)
+                .then(|| store.current_sync_committee.clone()),
+            next_sync_committee: changed(versions.next_sync_committee).then(|| store.next_sync_committee.clone()),
+            best_valid_update: changed(versions.best_valid_update).then(|| store.best_valid_update.clone()),
+            previous_max_active_participants: changed(versions.participants)
+                .then_some(store.previous_max_active_participants),
+            current_max_active_participants: changed(versions.participants)
+                .then_some(store.current_max_active_participants),
+        }))
+    }
+
+    fn get_optimistic_slot(&self) -> Option<u64> {
+        self.consensus.is_bootstrapped().then(|| self.consensus.get_optimistic_slot())
+    }
+
+    fn get_finalized_slot(&self) -> Option<u64> {
+        self.consensus.is_bootstrapped().then(|| self.consensus.get_finalized_slot())
+    }
+
+    fn get_latest_block_hash(&self) -> Result<String> {
+        Ok(self.consensus.get_latest_block_hash())
+    }
+
+    fn get_latest_block(&self, commitment: Commitment) -> Result<BlockHeader> {
+        let header = self.header(commitment)?;
+        Ok(BlockHeader::from_light_client_header(header, commitment))
+    }
+
+    fn get_header(&self, query: &HeaderQuery) -> Result<BlockHeader> {
+        let header = match query {
+            HeaderQuery::Slot(slot) => self.find_header(|header, _| header.beacon.slot == *slot),
+            HeaderQuery::Number(number) => self.find_header(|header, _| header.execution.block_number == *number),
+            HeaderQuery::Hash(hash) => {
+                let hash: B256 = hash
+                    .parse()
+                    .map_err(|_| LightClientError::InvalidArgument(format!("Invalid block hash {}.", hash)))?;
+                self.find_header(|header, _| header.execution.block_hash == hash)
+            }
+        };
+
+        let (header, commitment) =
+            header.ok_or(LightClientError::NotFound(format!("No verified header for {:?}.", query)))?;
+        Ok(BlockHeader::from_light_client_header(header, commitment))
     }
 
-    fn get_latest_block_hash(&self) -> String {
-        self.consensus.get_latest_block_hash()
+    fn verify_ancestry(&self, proof: &[u8]) -> Result<BlockHeader> {
+        let proof = AncestryProof::from_ssz_bytes(proof)
+            .map_err(|e| LightClientError::InvalidArgument(format!("Invalid ancestry proof: {:?}", e)))?;
+        let (anchor, _) = self
+            .find_header(|header, commitment| {
+                commitment == Commitment::Finalized && header.beacon.slot == proof.anchor_slot
+            })
+            .ok_or(LightClientError::NotFound(format!(
+                "Slot {} is not a verified finalized header.",
+                proof.anchor_slot
+            )))?;
+
+        self.consensus.verify_ancestry(&proof, &anchor.beacon)?;
+        Ok(BlockHeader::from_light_client_header(&proof.header, Commitment::Finalized))
     }
 
-    fn get_base_gas_fee(&self) -> u128 {
-        self.block.base_gas_fee
+    fn is_canonical(&self, block_number: u64, hash: &str) -> Result<bool> {
+        let hash: B256 = hash
+            .parse()
+            .map_err(|_| LightClientError::InvalidArgument(format!("Invalid block hash {}.", hash)))?;
+        if let Some(known) = self.canonical_hashes.get(&block_number) {
+            return Ok(*known == hash);
+        }
+
+        let (header, _) = self
+            .find_header(|header, commitment| {
+                commitment == Commitment::Finalized && header.execution.block_number == block_number
+            })
+            .ok_or(LightClientError::NotFound(format!(
+                "Block {} is not a finalized or backfilled block.",
+                block_number
+            )))?;
+        Ok(header.execution.block_hash == hash)
     }
 
-    fn get_max_priority_fee(&self) -> u128 {
-        self.block.max_priority_fee
+    fn get_base_gas_fee(&self) -> Result<u128> {
+        let header = self.header(Commitment::Optimistic)?;
+        Ok(header.execution.base_fee_per_gas.saturating_to())
+    }
+
+    fn get_next_base_gas_fee(&self) -> Result<u128> {
+        let header = self.header(Commitment::Optimistic)?;
+        Ok(next_base_fee_per_gas(&header.execution).saturating_to())
+    }
+
+    fn get_max_priority_fee(&self) -> Result<u128> {
+        self.header(Commitment::Optimistic)?;
+        Ok(self.block.max_priority_fee)
+    }
+
+    fn verify_account(&self, address: &str, block: Option<u64>) -> Result<Account> {
+        let (address, account) = self.proven_account(address)?;
+        if block.is_some_and(|block| block != account.block_number) {
+            let message = format!("Account {} is proven at block {}.", address, account.block_number);
+            return Err(LightClientError::NotFound(message).into());
+        }
+
+        Ok(Account {
+            address: address.to_string(),
+            block_number: account.block_number,
+            state_root: account.state_root.to_string(),
+            nonce: account.nonce,
+            balance: to_nat(account.balance)?,
+            storage_hash: account.storage_hash.to_string(),
+            code_hash: account.code_hash.to_string(),
+        })
+    }
+
+    fn get_storage_at(&self, address: &str, slot: &str) -> Result<StorageSlot> {
+        let (address, account) = self.proven_account(address)?;
+        let slot: B256 = slot
+            .parse()
+            .map_err(|_| LightClientError::InvalidArgument(format!("Invalid storage slot {}.", slot)))?;
+        let value = account
+            .storage
+            .get(&slot)
+            .ok_or(LightClientError::NotFound(format!("Slot {} of {} is not proven.", slot, address)))?;
+
+        Ok(StorageSlot {
+            address: address.to_string(),
+            slot: slot.to_string(),
+            block_number: account.block_number,
+            value: B256::from(*value).to_string(),
+        })
+    }
+
+    fn get_logs(&self, query: &LogQuery) -> Result<Vec<Log>> {
+        let parse = |value: &str| {
+            value
+                .parse::<B256>()
+                .map_err(|_| LightClientError::InvalidArgument(format!("Invalid topic {}.", value)))
+        };
+
+        let address = match &query.address {
+            Some(address) => Some(
+                address
+                    .parse::<Address>()
+                    .map_err(|_| LightClientError::InvalidArgument(format!("Invalid address {}.", address)))?,
+            ),
+            None => None,
+        };
+        let topics = query
+            .topics
+            .iter()
+            .map(|topic| topic.as_deref().map(parse).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let logs = self
+            .logs
+            .iter()
+            .filter(|proven| query.from_block.is_none_or(|from_block| proven.block_number >= from_block))
+            .filter(|proven| address.is_none_or(|address| proven.log.address == address))
+            .filter(|proven| {
+                topics
+                    .iter()
+                    .enumerate()
+                    .all(|(i, topic)| topic.is_none_or(|topic| proven.log.topics().get(i) == Some(&topic)))
+            })
+            .map(|proven| Log {
+                block_number: proven.block_number,
+                block_hash: proven.block_hash.to_string(),
+                transaction_index: proven.transaction_index,
+                log_index: proven.log_index,
+                commitment: if proven.finalized { Commitment::Finalized } else { Commitment::Optimistic },
+                address: proven.log.address.to_string(),
+                topics: proven.log.topics().iter().map(|topic| topic.to_string()).collect(),
+                data: proven.log.data.data.to_string(),
+            })
+            .collect();
+
+        Ok(logs)
+    }
+
+    fn verify_transaction(&self, tx_hash: &str, proof: TransactionProof) -> Result<Transaction> {
+        let tx_hash: B256 = tx_hash
+            .parse()
+            .map_err(|_| LightClientError::InvalidArgument(format!("Invalid transaction hash {}.", tx_hash)))?;
+        let block_hash = self
+            .header_at(proof.block_number)
+            .map_err(|_| {
+                LightClientError::NotFound(format!("Block {} is not a verified execution header.", proof.block_number))
+            })?
+            .execution
+            .block_hash;
+
+        let proof = EthereumTransactionProof {
+            block_number: proof.block_number,
+            index: proof.index,
+            header: proof.header.into(),
+            transaction: proof.transaction.into(),
+            proof: proof.proof.into_iter().map(Into::into).collect(),
+        };
+        let transaction = proof.verify(block_hash)?;
+        if *transaction.tx_hash() != tx_hash {
+            let message = format!("Proof is for transaction {}.", transaction.tx_hash());
+            return Err(LightClientError::InvalidProof(message).into());
+        }
+
+        let tx_type = match &transaction {
+            TxEnvelope::Legacy(_) => TransactionType::Legacy,
+            TxEnvelope::Eip2930(_) => TransactionType::Eip2930,
+            TxEnvelope::Eip1559(_) => TransactionType::Eip1559,
+            TxEnvelope::Eip4844(_) => TransactionType::Eip4844,
+            TxEnvelope::Eip7702(_) => TransactionType::Eip7702,
+        };
+
+        Ok(Transaction {
+            hash: tx_hash.to_string(),
+            block_number: proof.block_number,
+            block_hash: block_hash.to_string(),
+            index: proof.index,
+            tx_type,
+            chain_id: transaction.chain_id(),
+            nonce: transaction.nonce(),
+            to: transaction.to().map(|to| to.to_string()),
+            value: to_nat(transaction.value())?,
+            input: transaction.input().to_string(),
+            gas_limit: transaction.gas_limit(),
+            max_fee_per_gas: transaction.max_fee_per_gas(),
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas(),
+        })
+    }
+
+    fn get_chain_timestamp(&self) -> Option<u64> {
+        let header = self.consensus.get_optimistic_header()?;
+        Some(header.execution.timestamp)
+    }
+
+    fn get_freshness_sla(&self) -> Option<u64> {
+        self.consensus.get_config().freshness_sla
+    }
+
+    fn get_tasks(&self) -> Vec<(ChainTask, Duration)> {
+        match self.consensus.get_config().force_update_interval {
+            0 => vec![],
+            interval => vec![(ChainTask::ForceUpdate, Duration::from_secs(interval))],
+        }
+    }
+
+    fn run_task(&mut self, task: ChainTask) -> Result<bool> {
+        match task {
+            ChainTask::ForceUpdate => {
+                let updated = self.consensus.force_update(ic_cdk::api::time());
+                if updated {
+                    self.record_headers();
+                }
+
+                Ok(updated)
+            }
+        }
     }
 }
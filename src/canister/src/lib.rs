@@ -1,81 +1,292 @@
+mod auth;
 mod blueprint;
 mod chain;
 mod config;
+mod error;
 mod ethereum;
 mod metrics;
 mod outcalls;
 mod state;
+mod storage;
+mod subscriptions;
+mod timers;
+mod types;
+mod uploads;
 
 use crate::config::ConfigManager;
-use ic_lightclient_wire::{StatePayloadMarshaller, UpdatePayloadParser};
+use auth::AccessControl;
+use candid::Principal;
+use error::LightClientError;
+use ic_lightclient_wire::{
+    ChainId, ChainStateDigest, ChainWireVersions, StatePayloadMarshaller, SupportedWireVersions, UpdatePayloadParser,
+    VersionRange,
+};
 use metrics::{serve_metrics, HttpRequest, HttpResponse};
+use outcalls::PendingBlockVotes;
 use state::GlobalState;
+use storage::StableStorage;
+use subscriptions::{Subscription, SubscriptionFilter, SubscriptionRegistry};
+use timers::TimerScheduler;
+use types::{
+    Account, BlockHeader, ChainUpdateReport, Commitment, Freshness, HeaderQuery, Log, LogQuery, StorageSlot,
+    Transaction, TransactionProof,
+};
+use uploads::UploadRegistry;
 
 #[ic_cdk::query]
-fn get_latest_block_hash(chain: u16) -> String {
-    let state = GlobalState::state().unwrap();
+fn get_latest_block_hash(chain: ChainId, max_age: Option<u64>) -> Result<String, LightClientError> {
+    let state = GlobalState::state()?;
     let state = state.borrow();
-    let chain = state.chains.get(&chain).unwrap();
-    chain.get_latest_block_hash()
+    let chain = state.chain(chain)?;
+    chain.ensure_fresh(max_age)?;
+    Ok(chain.get_latest_block_hash()?)
 }
 
 #[ic_cdk::query]
-fn get_base_gas_fee(chain: u16) -> u128 {
-    let state = GlobalState::state().unwrap();
+fn get_latest_block(
+    chain: ChainId,
+    commitment: Commitment,
+    max_age: Option<u64>,
+) -> Result<BlockHeader, LightClientError> {
+    let state = GlobalState::state()?;
     let state = state.borrow();
-    let chain = state.chains.get(&chain).unwrap();
-    chain.get_base_gas_fee()
+    let chain = state.chain(chain)?;
+    chain.ensure_fresh(max_age)?;
+    Ok(chain.get_latest_block(commitment)?)
 }
 
 #[ic_cdk::query]
-fn get_max_priority_fee(chain: u16) -> u128 {
-    let state = GlobalState::state().unwrap();
+fn get_header(chain: ChainId, query: HeaderQuery) -> Result<BlockHeader, LightClientError> {
+    let state = GlobalState::state()?;
     let state = state.borrow();
-    let chain = state.chains.get(&chain).unwrap();
-    chain.get_max_priority_fee()
+    let chain = state.chain(chain)?;
+    Ok(chain.get_header(&query)?)
 }
 
+/// Verifies an SSZ encoded ancestry proof of a header older than the header history.
 #[ic_cdk::query]
-fn get_state() -> Vec<u8> {
-    let state = GlobalState::state().unwrap();
+fn verify_ancestry(chain: ChainId, proof: Vec<u8>) -> Result<BlockHeader, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.verify_ancestry(&proof)?)
+}
+
+/// Whether `hash` is the finalized execution block at `block_number`, either a verified header or
+/// a hash backfilled through its `parent_hash` links.
+#[ic_cdk::query]
+fn is_canonical(chain: ChainId, block_number: u64, hash: String) -> Result<bool, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.is_canonical(block_number, &hash)?)
+}
+
+#[ic_cdk::query]
+fn get_base_gas_fee(chain: ChainId, max_age: Option<u64>) -> Result<u128, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    chain.ensure_fresh(max_age)?;
+    Ok(chain.get_base_gas_fee()?)
+}
+
+#[ic_cdk::query]
+fn get_next_base_gas_fee(chain: ChainId, max_age: Option<u64>) -> Result<u128, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    chain.ensure_fresh(max_age)?;
+    Ok(chain.get_next_base_gas_fee()?)
+}
+
+#[ic_cdk::query]
+fn get_max_priority_fee(chain: ChainId, max_age: Option<u64>) -> Result<u128, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    chain.ensure_fresh(max_age)?;
+    Ok(chain.get_max_priority_fee()?)
+}
+
+#[ic_cdk::query]
+fn get_freshness(chain: ChainId) -> Result<Freshness, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.get_freshness())
+}
+
+#[ic_cdk::query]
+fn get_pending_votes(chain: ChainId) -> Result<Vec<PendingBlockVotes>, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.get_pending_votes()?)
+}
+
+/// State of a watched account, verified against the state root of the verified header at its block
+/// when the agent pushed the proof. Fails if `block` is set and the account is proven at another.
+#[ic_cdk::query]
+fn verify_account(chain: ChainId, address: String, block: Option<u64>) -> Result<Account, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.verify_account(&address, block)?)
+}
+
+/// Storage slot of a watched account, proven along with the account state.
+#[ic_cdk::query]
+fn get_storage_at(chain: ChainId, address: String, slot: String) -> Result<StorageSlot, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.get_storage_at(&address, &slot)?)
+}
+
+#[ic_cdk::query]
+fn get_logs(chain: ChainId, query: LogQuery) -> Result<Vec<Log>, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.get_logs(&query)?)
+}
+
+#[ic_cdk::query]
+fn verify_transaction(
+    chain: ChainId,
+    tx_hash: String,
+    proof: TransactionProof,
+) -> Result<Transaction, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(chain)?;
+    Ok(chain.verify_transaction(&tx_hash, proof)?)
+}
+
+#[ic_cdk::query]
+fn get_state() -> Result<Vec<u8>, LightClientError> {
+    let state = GlobalState::state()?;
     let state = state.borrow();
     let mut marshaller = StatePayloadMarshaller::new();
 
     for chain in state.chains.values() {
-        chain.get_state(&mut marshaller).unwrap();
+        chain.get_state(&mut marshaller)?;
     }
 
-    marshaller.build().unwrap()
+    Ok(marshaller.build()?)
+}
+
+/// Versions and state hashes of every chain, for agents to skip fetching unchanged states.
+#[ic_cdk::query]
+fn get_state_digest() -> Result<Vec<ChainStateDigest>, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let mut digests: Vec<ChainStateDigest> = state.chains.values().map(|chain| chain.get_state_digest()).collect();
+    digests.sort_by_key(|digest| digest.chain);
+
+    Ok(digests)
 }
 
+/// Envelope and per-chain payload versions accepted by `update_state`, for agents to negotiate.
 #[ic_cdk::query]
-fn list_chain_uids() -> Vec<u16> {
-    GlobalState::chain_uids().unwrap()
+fn supported_wire_versions() -> Result<SupportedWireVersions, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let mut chains: Vec<ChainWireVersions> = state.chains.values().map(|chain| chain.get_wire_versions()).collect();
+    chains.sort_by_key(|chain| chain.chain);
+
+    Ok(SupportedWireVersions { envelope: VersionRange::envelope(), chains })
 }
 
+/// State of `uid` as a marshalled `ChainStateDelta` against the version the caller holds.
 #[ic_cdk::query]
-fn get_chain_config(uid: u16) -> Vec<u8> {
-    let state = GlobalState::state().unwrap();
+fn get_chain_state(uid: ChainId, since_version: Option<u64>) -> Result<Vec<u8>, LightClientError> {
+    let state = GlobalState::state()?;
     let state = state.borrow();
-    let chain = state.chains.get(&uid).unwrap();
-    chain.get_config().unwrap()
+    let chain = state.chain(uid)?;
+    Ok(chain.get_state_delta(since_version)?)
+}
+
+#[ic_cdk::query]
+fn list_chain_uids() -> Result<Vec<ChainId>, LightClientError> {
+    Ok(GlobalState::chain_uids()?)
+}
+
+#[ic_cdk::query]
+fn get_chain_config(uid: ChainId) -> Result<Vec<u8>, LightClientError> {
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(uid)?;
+    Ok(chain.get_config()?)
 }
 
 #[ic_cdk::update]
-fn update_state(updates: Vec<u8>) {
+fn update_state(updates: Vec<u8>) -> Result<Vec<ChainUpdateReport>, LightClientError> {
+    apply_updates(updates)
+}
+
+/// Starts an upload session for an update payload too large for a single ingress message.
+#[ic_cdk::update]
+fn begin_upload(session: u64, total_len: u64, sha256: Vec<u8>) -> Result<(), LightClientError> {
+    AccessControl::require_any_agent()?;
+    UploadRegistry::begin(ic_cdk::api::caller(), session, total_len, sha256)
+}
+
+/// Appends the next chunk of the upload and returns the number of bytes received so far.
+#[ic_cdk::update]
+fn append_upload(session: u64, chunk: Vec<u8>) -> Result<u64, LightClientError> {
+    UploadRegistry::append(ic_cdk::api::caller(), session, &chunk)
+}
+
+/// Applies the reassembled update payload of the upload as `update_state` would.
+#[ic_cdk::update]
+fn commit_upload(session: u64) -> Result<Vec<ChainUpdateReport>, LightClientError> {
+    let updates = UploadRegistry::commit(ic_cdk::api::caller(), session)?;
+    apply_updates(updates)
+}
+
+fn apply_updates(updates: Vec<u8>) -> Result<Vec<ChainUpdateReport>, LightClientError> {
     let start = ic_cdk::api::performance_counter(0);
 
-    let parser = UpdatePayloadParser::new(updates).unwrap();
-    let state = GlobalState::state().unwrap();
+    // Chains are only known once parsed, so callers which aren't an agent of any chain are
+    // rejected before spending instructions on parsing.
+    AccessControl::require_any_agent()?;
+
+    let parser = UpdatePayloadParser::new(updates).map_err(|e| LightClientError::InvalidPayload(e.to_string()))?;
+    let mut uids = parser.chain_uids();
+    uids.sort();
+
+    for uid in uids.iter() {
+        AccessControl::require_agent(*uid)?;
+    }
+
+    let state = GlobalState::state()?;
     let mut state = state.borrow_mut();
 
-    for chain in state.chains.values_mut() {
-        chain.update_state(&parser).unwrap();
+    for uid in uids.iter() {
+        state.chain(*uid)?;
+    }
+
+    let mut reports = vec![];
+    for uid in uids {
+        let chain = state.chain_mut(uid)?;
+        let report = chain.update_state(&parser);
+
+        if report.applied > 0 {
+            SubscriptionRegistry::publish(uid, chain);
+        }
+
+        reports.push(report);
     }
 
+    SubscriptionRegistry::dispatch();
+
     let end = ic_cdk::api::performance_counter(0);
     let cycles = ic_cdk::api::canister_balance();
     ic_cdk::println!("Instructions: {}, cycles: {}", end - start, cycles);
+
+    Ok(reports)
 }
 
 #[ic_cdk::query]
@@ -84,23 +295,116 @@ fn http_request(_: HttpRequest) -> HttpResponse {
 }
 
 #[ic_cdk::update]
-async fn init(chains: Vec<u16>) {
-    GlobalState::init(chains).await.unwrap();
+async fn init(chains: Vec<ChainId>) -> Result<(), LightClientError> {
+    AccessControl::require_admin()?;
+    GlobalState::init(chains).await?;
+    TimerScheduler::start()?;
+    Ok(())
 }
 
 #[ic_cdk::update]
-fn set_config(chain: u16, config: String) {
+fn set_config(chain: ChainId, config: String) -> Result<(), LightClientError> {
+    AccessControl::require_admin()?;
     ConfigManager::set(chain, config);
+    Ok(())
 }
 
 #[ic_cdk::query]
-fn list_configs() -> Vec<u16> {
+fn list_configs() -> Vec<ChainId> {
     ConfigManager::list()
 }
 
 #[ic_cdk::query]
-fn get_config(chain: u16) -> Option<String> {
+fn get_config(chain: ChainId) -> Option<String> {
     ConfigManager::get(chain)
 }
 
+#[ic_cdk::update]
+fn add_admin(principal: Principal) -> Result<(), LightClientError> {
+    AccessControl::require_controller()?;
+    AccessControl::add_admin(principal);
+    Ok(())
+}
+
+#[ic_cdk::update]
+fn remove_admin(principal: Principal) -> Result<(), LightClientError> {
+    AccessControl::require_controller()?;
+    AccessControl::remove_admin(&principal);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn list_admins() -> Vec<Principal> {
+    AccessControl::list_admins()
+}
+
+#[ic_cdk::update]
+fn add_agent(chain: ChainId, principal: Principal) -> Result<(), LightClientError> {
+    AccessControl::require_admin()?;
+    AccessControl::add_agent(chain, principal);
+    Ok(())
+}
+
+#[ic_cdk::update]
+fn remove_agent(chain: ChainId, principal: Principal) -> Result<(), LightClientError> {
+    AccessControl::require_admin()?;
+    AccessControl::remove_agent(chain, &principal);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn list_agents(chain: ChainId) -> Vec<Principal> {
+    AccessControl::list_agents(chain)
+}
+
+/// Registers the calling canister's `method` to be notified with a `HeaderNotification` when a new
+/// header matching `filter` is verified.
+#[ic_cdk::update]
+fn subscribe(method: String, filter: SubscriptionFilter) -> Result<u64, LightClientError> {
+    AccessControl::require_canister()?;
+
+    let state = GlobalState::state()?;
+    let state = state.borrow();
+    let chain = state.chain(filter.chain)?;
+
+    // Only chains serving structured headers can notify subscribers.
+    if let Err(err) = chain.get_latest_block(filter.commitment).map_err(LightClientError::from) {
+        if matches!(err, LightClientError::Unsupported(_)) {
+            return Err(err);
+        }
+    }
+
+    SubscriptionRegistry::subscribe(ic_cdk::api::caller(), method, filter)
+}
+
+#[ic_cdk::update]
+fn unsubscribe(id: u64) -> Result<(), LightClientError> {
+    let subscription = SubscriptionRegistry::get(id).ok_or(LightClientError::UnknownSubscription(id))?;
+    if subscription.subscriber != ic_cdk::api::caller() {
+        AccessControl::require_admin()?;
+    }
+
+    SubscriptionRegistry::unsubscribe(id);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn list_subscriptions() -> Vec<Subscription> {
+    SubscriptionRegistry::list(ic_cdk::api::caller())
+}
+
+#[ic_cdk::pre_upgrade]
+fn pre_upgrade() {
+    StableStorage::save().expect("Failed to save canister state");
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    StableStorage::restore().expect("Failed to restore canister state");
+
+    if GlobalState::is_initialized() {
+        TimerScheduler::start().expect("Failed to start timers");
+    }
+}
+
 ic_cdk::export_candid!();
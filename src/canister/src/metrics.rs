@@ -1,3 +1,4 @@
+use crate::state::GlobalState;
 use candid::CandidType;
 use ic_cdk::api::{canister_balance128, time};
 use serde::Deserialize;
@@ -23,12 +24,14 @@ pub struct HttpResponse {
 pub fn serve_metrics() -> HttpResponse {
     let timestamp = time() / 1000000;
 
-    let body = vec![
+    let mut body = vec![
         "# HELP cycle_balance Current cycle balance of canister".to_string(),
         "# TYPE cycle_balance counter".to_string(),
         format!("cycle_balance {} {}", canister_balance128(), timestamp),
-    ]
-    .join("\n");
+    ];
+
+    body.extend(freshness_metrics(timestamp));
+    let body = body.join("\n");
 
     let body = body.as_bytes().to_vec();
 
@@ -42,3 +45,37 @@ pub fn serve_metrics() -> HttpResponse {
         body: body,
     }
 }
+
+fn freshness_metrics(timestamp: u64) -> Vec<String> {
+    let Ok(state) = GlobalState::state() else { return vec![] };
+    let state = state.borrow();
+
+    let mut ages = vec![
+        "# HELP chain_data_age_seconds Age of the data served for the chain".to_string(),
+        "# TYPE chain_data_age_seconds gauge".to_string(),
+    ];
+    let mut slas = vec![
+        "# HELP chain_freshness_sla_seconds Configured maximum age of the data served for the chain".to_string(),
+        "# TYPE chain_freshness_sla_seconds gauge".to_string(),
+    ];
+    let mut within_slas = vec![
+        "# HELP chain_within_freshness_sla Whether the data served for the chain meets its freshness SLA".to_string(),
+        "# TYPE chain_within_freshness_sla gauge".to_string(),
+    ];
+
+    for (uid, chain) in state.chains.iter() {
+        let freshness = chain.get_freshness();
+
+        if let Some(age) = freshness.age {
+            ages.push(format!("chain_data_age_seconds{{chain=\"{}\"}} {} {}", uid, age, timestamp));
+        }
+
+        if let Some(sla) = freshness.freshness_sla {
+            let within_sla = freshness.is_within(sla) as u8;
+            slas.push(format!("chain_freshness_sla_seconds{{chain=\"{}\"}} {} {}", uid, sla, timestamp));
+            within_slas.push(format!("chain_within_freshness_sla{{chain=\"{}\"}} {} {}", uid, within_sla, timestamp));
+        }
+    }
+
+    [ages, slas, within_slas].concat()
+}
@@ -1,5 +1,7 @@
 mod config;
+mod quorum;
 mod state;
 
 pub use config::OutcallsConfigManager;
+pub use quorum::PendingBlockVotes;
 pub use state::OutcallsStateManager;
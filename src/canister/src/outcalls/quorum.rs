@@ -0,0 +1,163 @@
+use crate::error::LightClientError;
+use candid::{CandidType, Principal};
+use ic_lightclient_wire::ethereum::outcalls::Block;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+const MAX_PENDING_BLOCKS: usize = 64;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BlockVote {
+    pub agent: Principal,
+    pub block_hash: String,
+    pub base_gas_fee: u128,
+    pub max_priority_fee: u128,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PendingBlockVotes {
+    pub block_num: u128,
+    pub votes: Vec<BlockVote>,
+    pub disagreement: bool,
+}
+
+/// Buffers block reports from agents until `threshold` of them agree on the same block.
+pub struct BlockQuorum {
+    threshold: usize,
+    pending: BTreeMap<u128, HashMap<Principal, Block>>,
+}
+
+impl BlockQuorum {
+    pub fn new(threshold: usize) -> Self {
+        Self { threshold: threshold.max(1), pending: BTreeMap::new() }
+    }
+
+    /// Records the vote of `agent` and returns the block once it reaches quorum. Later votes from
+    /// the same agent for the same block number replace the earlier ones. Votes for blocks up to
+    /// the `committed` one can't reach quorum anymore and are rejected as stale.
+    pub fn submit(
+        &mut self,
+        agent: Principal,
+        block: Block,
+        committed: &Block,
+    ) -> Result<Option<Block>, LightClientError> {
+        if block.block_num <= committed.block_num {
+            return Err(LightClientError::Stale { age: committed.timestamp.checked_sub(block.timestamp), max_age: 0 });
+        }
+
+        let block_num = block.block_num;
+        let votes = self.pending.entry(block_num).or_default();
+        votes.insert(agent, block.clone());
+
+        let agreeing = votes.values().filter(|vote| is_same_block(vote, &block)).count();
+        if agreeing >= self.threshold {
+            self.prune(block_num);
+            return Ok(Some(block));
+        }
+
+        while self.pending.len() > MAX_PENDING_BLOCKS {
+            self.pending.pop_first();
+        }
+
+        Ok(None)
+    }
+
+    pub fn pending(&self) -> Vec<PendingBlockVotes> {
+        self.pending
+            .iter()
+            .map(|(block_num, votes)| {
+                let disagreement = votes
+                    .values()
+                    .any(|vote| votes.values().any(|other| !is_same_block(vote, other)));
+                let votes = votes
+                    .iter()
+                    .map(|(agent, vote)| BlockVote {
+                        agent: *agent,
+                        block_hash: vote.block_hash.clone(),
+                        base_gas_fee: vote.base_gas_fee,
+                        max_priority_fee: vote.max_priority_fee,
+                    })
+                    .collect();
+
+                PendingBlockVotes { block_num: *block_num, votes, disagreement }
+            })
+            .collect()
+    }
+
+    fn prune(&mut self, committed: u128) {
+        self.pending = self.pending.split_off(&(committed + 1));
+    }
+}
+
+/// Agents observe the block at different times, so the timestamps of their reports are not compared.
+fn is_same_block(a: &Block, b: &Block) -> bool {
+    a.block_num == b.block_num
+        && a.block_hash == b.block_hash
+        && a.base_gas_fee == b.base_gas_fee
+        && a.max_priority_fee == b.max_priority_fee
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockQuorum;
+    use crate::error::LightClientError;
+    use candid::Principal;
+    use ic_lightclient_wire::ethereum::outcalls::Block;
+
+    fn block(block_num: u128, block_hash: &str) -> Block {
+        Block { block_num, block_hash: block_hash.to_string(), base_gas_fee: 10, max_priority_fee: 1, timestamp: 0 }
+    }
+
+    #[test]
+    fn test_quorum_ignores_timestamp() {
+        let agents: Vec<Principal> = (0..2u8).map(|i| Principal::from_slice(&[i])).collect();
+        let mut quorum = BlockQuorum::new(2);
+        let committed = Block::default();
+
+        assert!(quorum
+            .submit(agents[0], Block { timestamp: 100, ..block(10, "0xa") }, &committed)
+            .unwrap()
+            .is_none());
+        assert!(quorum
+            .submit(agents[1], Block { timestamp: 101, ..block(10, "0xa") }, &committed)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_quorum_commit() {
+        let agents: Vec<Principal> = (0..3u8).map(|i| Principal::from_slice(&[i])).collect();
+        let mut quorum = BlockQuorum::new(2);
+        let genesis = Block::default();
+
+        assert!(quorum.submit(agents[0], block(10, "0xa"), &genesis).unwrap().is_none());
+        assert!(quorum.submit(agents[0], block(10, "0xa"), &genesis).unwrap().is_none());
+        assert!(quorum.submit(agents[1], block(10, "0xb"), &genesis).unwrap().is_none());
+
+        let pending = quorum.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].votes.len(), 2);
+        assert!(pending[0].disagreement);
+
+        let committed = quorum.submit(agents[2], block(10, "0xa"), &genesis).unwrap();
+        assert_eq!(committed.map(|b| b.block_hash), Some("0xa".to_string()));
+        assert!(quorum.pending().is_empty());
+    }
+
+    #[test]
+    fn test_quorum_rejects_stale_votes() {
+        let agent = Principal::from_slice(&[0]);
+        let mut quorum = BlockQuorum::new(2);
+        let committed = Block { timestamp: 120, ..block(10, "0xa") };
+
+        let older = Block { timestamp: 108, ..block(9, "0xc") };
+        assert!(matches!(
+            quorum.submit(agent, older, &committed),
+            Err(LightClientError::Stale { age: Some(12), max_age: 0 })
+        ));
+
+        let equivocating = Block { timestamp: 120, ..block(10, "0xb") };
+        assert!(matches!(quorum.submit(agent, equivocating, &committed), Err(LightClientError::Stale { .. })));
+        assert!(quorum.pending().is_empty());
+    }
+}
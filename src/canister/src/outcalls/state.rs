@@ -1,45 +1,76 @@
-use crate::chain::StateManager;
+use crate::{
+    chain::{StateManager, UpdateOutcome},
+    outcalls::quorum::{BlockQuorum, PendingBlockVotes},
+};
 use anyhow::Result;
 use ic_lightclient_wire::ethereum::outcalls::{Block, Config};
 
 pub struct OutcallsStateManager {
-    _config: Config,
+    config: Config,
     state: Block,
+    quorum: BlockQuorum,
 }
 
 impl StateManager for OutcallsStateManager {
     type Config = Config;
     type StatePayload = Block;
     type UpdatePayload = Block;
+    type StateDiff = Block;
+    type StableState = Block;
 
     fn new(config: Config) -> Self {
-        Self { _config: config, state: Block::default() }
+        let quorum = BlockQuorum::new(config.quorum_threshold as usize);
+        Self { config, state: Block::default(), quorum }
+    }
+
+    fn restore(config: Config, state: Block) -> Self {
+        let quorum = BlockQuorum::new(config.quorum_threshold as usize);
+        Self { config, state, quorum }
+    }
+
+    /// Only the committed block is persisted, an upgrade resets the votes of blocks still short of
+    /// quorum and agents have to submit them again.
+    fn stable_state(&self) -> Result<Block> {
+        Ok(self.state.clone())
     }
 
     fn get_state(&self) -> Result<Block> {
         Ok(self.state.clone())
     }
 
-    fn update_state(&mut self, updates: Vec<Block>) -> Result<()> {
-        let mut updates = updates;
-        let block = updates.pop();
+    fn update_state(&mut self, block: Block) -> Result<UpdateOutcome> {
+        let agent = ic_cdk::api::caller();
 
-        if let Some(block) = block {
-            self.state = block;
+        match self.quorum.submit(agent, block, &self.state)? {
+            Some(block) => {
+                self.state = block;
+                Ok(UpdateOutcome::Applied)
+            }
+            None => Ok(UpdateOutcome::Pending),
         }
+    }
+
+    fn get_latest_block_hash(&self) -> Result<String> {
+        Ok(self.state.block_hash.clone())
+    }
+
+    fn get_base_gas_fee(&self) -> Result<u128> {
+        Ok(self.state.base_gas_fee)
+    }
 
-        Ok(())
+    fn get_max_priority_fee(&self) -> Result<u128> {
+        Ok(self.state.max_priority_fee)
     }
 
-    fn get_latest_block_hash(&self) -> String {
-        self.state.block_hash.clone()
+    fn get_chain_timestamp(&self) -> Option<u64> {
+        (self.state.timestamp > 0).then_some(self.state.timestamp)
     }
 
-    fn get_base_gas_fee(&self) -> u128 {
-        self.state.base_gas_fee
+    fn get_freshness_sla(&self) -> Option<u64> {
+        self.config.freshness_sla
     }
 
-    fn get_max_priority_fee(&self) -> u128 {
-        self.state.max_priority_fee
+    fn get_pending_votes(&self) -> Result<Vec<PendingBlockVotes>> {
+        Ok(self.quorum.pending())
     }
 }
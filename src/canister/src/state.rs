@@ -1,5 +1,11 @@
-use crate::{blueprint::build_chain_from_uid, chain::Chain};
+use crate::{
+    blueprint::{build_chain_from_uid, restore_chain_from_uid},
+    chain::Chain,
+    error::LightClientError,
+    storage::StableChain,
+};
 use anyhow::{anyhow, Result};
+use ic_lightclient_wire::ChainId;
 use std::{
     cell::{OnceCell, RefCell},
     collections::HashMap,
@@ -11,13 +17,25 @@ thread_local! {
 }
 
 pub struct ChainState {
-    pub chains: HashMap<u16, Box<dyn Chain>>,
+    pub chains: HashMap<ChainId, Box<dyn Chain>>,
+}
+
+impl ChainState {
+    pub fn chain(&self, uid: ChainId) -> Result<&dyn Chain> {
+        let chain = self.chains.get(&uid).ok_or(LightClientError::UnknownChain(uid))?;
+        Ok(chain.as_ref())
+    }
+
+    pub fn chain_mut(&mut self, uid: ChainId) -> Result<&mut dyn Chain> {
+        let chain = self.chains.get_mut(&uid).ok_or(LightClientError::UnknownChain(uid))?;
+        Ok(chain.as_mut())
+    }
 }
 
 pub struct GlobalState;
 
 impl GlobalState {
-    pub async fn init(uids: Vec<u16>) -> Result<()> {
+    pub async fn init(uids: Vec<ChainId>) -> Result<()> {
         let mut chains = HashMap::new();
 
         for uid in uids {
@@ -27,6 +45,22 @@ impl GlobalState {
             chains.insert(uid, chain);
         }
 
+        Self::set(chains)
+    }
+
+    pub fn restore(stable_chains: Vec<StableChain>) -> Result<()> {
+        let mut chains = HashMap::new();
+
+        for stable_chain in stable_chains {
+            let uid = stable_chain.uid;
+            let chain = restore_chain_from_uid(stable_chain)?;
+            chains.insert(uid, chain);
+        }
+
+        Self::set(chains)
+    }
+
+    fn set(chains: HashMap<ChainId, Box<dyn Chain>>) -> Result<()> {
         let chains = ChainState { chains };
         let chains = Rc::new(RefCell::new(chains));
 
@@ -38,20 +72,24 @@ impl GlobalState {
         Ok(())
     }
 
+    pub fn is_initialized() -> bool {
+        CHAINS.with(|chains| chains.get().is_some())
+    }
+
     pub fn state() -> Result<Rc<RefCell<ChainState>>> {
         CHAINS.with(|chains| {
-            let chains = chains.get().ok_or(anyhow!("Global state not initialized"))?;
+            let chains = chains.get().ok_or(LightClientError::NotInitialized)?;
             Ok(chains.clone())
         })
     }
 
-    pub fn chain_uids() -> Result<Vec<u16>> {
+    pub fn chain_uids() -> Result<Vec<ChainId>> {
         CHAINS.with(|state| {
-            let state = state.get().ok_or(anyhow!("Global state not initialized"))?;
+            let state = state.get().ok_or(LightClientError::NotInitialized)?;
 
             let state = state.borrow();
             let chains = &state.chains;
-            let uids = chains.keys().map(|k| k.clone()).collect();
+            let uids = chains.keys().copied().collect();
 
             Ok(uids)
         })
@@ -0,0 +1,82 @@
+use crate::{
+    auth::{AccessControl, Roles},
+    config::ConfigManager,
+    state::GlobalState,
+    subscriptions::{SubscriptionRegistry, Subscriptions},
+};
+use anyhow::{anyhow, Result};
+use candid::CandidType;
+use ic_cdk::{
+    api::stable::stable_size,
+    storage::{stable_restore, stable_save},
+};
+use ic_lightclient_wire::ChainId;
+use serde::Deserialize;
+
+#[derive(CandidType, Deserialize)]
+pub struct StableChain {
+    pub uid: ChainId,
+    pub config: Vec<u8>,
+    pub state: Vec<u8>,
+    pub last_updated: Option<u64>,
+    pub version: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct StableState {
+    configs: Vec<(ChainId, String)>,
+    roles: Roles,
+    chains: Option<Vec<StableChain>>,
+    subscriptions: Option<Subscriptions>,
+}
+
+pub struct StableStorage;
+
+impl StableStorage {
+    pub fn save() -> Result<()> {
+        let chains = if GlobalState::is_initialized() {
+            let state = GlobalState::state()?;
+            let state = state.borrow();
+            let mut chains = vec![];
+
+            for (uid, chain) in state.chains.iter() {
+                let config = chain.get_config()?;
+                let state = chain.get_stable_state()?;
+                let last_updated = chain.get_freshness().last_updated;
+                let version = Some(chain.get_state_digest().version);
+                chains.push(StableChain { uid: *uid, config, state, last_updated, version });
+            }
+
+            Some(chains)
+        } else {
+            None
+        };
+
+        let state = StableState {
+            configs: ConfigManager::entries(),
+            roles: AccessControl::roles(),
+            chains,
+            subscriptions: Some(SubscriptionRegistry::subscriptions()),
+        };
+        stable_save((state,)).map_err(|e| anyhow!("Failed to write stable memory: {}", e))
+    }
+
+    pub fn restore() -> Result<()> {
+        // Nothing to restore when upgrading from a version without upgrade hooks.
+        if stable_size() == 0 {
+            return Ok(());
+        }
+
+        let (state,): (StableState,) = stable_restore().map_err(|e| anyhow!("Failed to read stable memory: {}", e))?;
+
+        ConfigManager::restore(state.configs);
+        AccessControl::restore(state.roles);
+        SubscriptionRegistry::restore(state.subscriptions.unwrap_or_default());
+
+        if let Some(chains) = state.chains {
+            GlobalState::restore(chains)?;
+        }
+
+        Ok(())
+    }
+}
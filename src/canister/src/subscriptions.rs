@@ -0,0 +1,275 @@
+use crate::{
+    chain::Chain,
+    error::LightClientError,
+    types::{BlockHeader, Commitment},
+};
+use candid::{CandidType, Principal};
+use ic_cdk::api::call::notify;
+use ic_lightclient_wire::ChainId;
+use serde::Deserialize;
+use std::{cell::RefCell, collections::BTreeMap};
+
+const MAX_SUBSCRIPTIONS: usize = 1024;
+const MAX_SUBSCRIPTIONS_PER_SUBSCRIBER: usize = 16;
+const MAX_NOTIFICATIONS_PER_DISPATCH: usize = 32;
+const MAX_DELIVERY_FAILURES: u32 = 5;
+const BASE_RETRY_DELAY_NS: u64 = 10_000_000_000;
+
+thread_local! {
+    static SUBSCRIPTIONS: RefCell<Subscriptions> = RefCell::new(Subscriptions::default());
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubscriptionFilter {
+    pub chain: ChainId,
+    pub commitment: Commitment,
+    /// Minimum number of execution blocks between two notifications, 0 and 1 notify on every block.
+    pub every_n_blocks: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Subscription {
+    pub id: u64,
+    pub subscriber: Principal,
+    pub method: String,
+    pub filter: SubscriptionFilter,
+    pub last_notified_block: Option<u64>,
+    pub pending: Option<BlockHeader>,
+    pub failures: u32,
+    /// IC time in nanoseconds before which a failed notification isn't retried.
+    pub retry_at: u64,
+}
+
+/// Argument of the notification sent to `Subscription::method`.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HeaderNotification {
+    pub subscription_id: u64,
+    pub chain: ChainId,
+    pub header: BlockHeader,
+}
+
+#[derive(CandidType, Deserialize, Clone, Default)]
+pub struct Subscriptions {
+    next_id: u64,
+    subscriptions: BTreeMap<u64, Subscription>,
+}
+
+impl Subscriptions {
+    fn subscribe(
+        &mut self,
+        subscriber: Principal,
+        method: String,
+        filter: SubscriptionFilter,
+    ) -> Result<u64, LightClientError> {
+        let owned = self.subscriptions.values().filter(|s| s.subscriber == subscriber).count();
+        if owned >= MAX_SUBSCRIPTIONS_PER_SUBSCRIBER || self.subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            return Err(LightClientError::LimitExceeded("subscriptions".into()));
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let subscription = Subscription {
+            id,
+            subscriber,
+            method,
+            filter,
+            last_notified_block: None,
+            pending: None,
+            failures: 0,
+            retry_at: 0,
+        };
+        self.subscriptions.insert(id, subscription);
+
+        Ok(id)
+    }
+
+    /// Queues `header` for the matching subscriptions, replacing any older undelivered header.
+    fn publish(&mut self, chain: ChainId, header: &BlockHeader) {
+        for subscription in self.subscriptions.values_mut() {
+            let filter = &subscription.filter;
+            if filter.chain != chain || filter.commitment != header.commitment {
+                continue;
+            }
+
+            let block_number = header.execution_block_number;
+            let is_due = subscription
+                .last_notified_block
+                .is_none_or(|last| block_number >= last + filter.every_n_blocks.max(1));
+
+            if is_due {
+                subscription.pending = Some(header.clone());
+            }
+        }
+    }
+
+    fn due(&self, current_time: u64, limit: usize) -> Vec<(Principal, String, HeaderNotification)> {
+        self.subscriptions
+            .values()
+            .filter(|subscription| subscription.retry_at <= current_time)
+            .filter_map(|subscription| {
+                let header = subscription.pending.clone()?;
+                let notification =
+                    HeaderNotification { subscription_id: subscription.id, chain: subscription.filter.chain, header };
+
+                Some((subscription.subscriber, subscription.method.clone(), notification))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Failed deliveries are retried with exponential backoff until the subscription is dropped
+    /// after `MAX_DELIVERY_FAILURES` consecutive failures.
+    fn record_delivery(&mut self, id: u64, delivered: bool, current_time: u64) {
+        let Some(subscription) = self.subscriptions.get_mut(&id) else { return };
+
+        if delivered {
+            subscription.last_notified_block = subscription.pending.take().map(|h| h.execution_block_number);
+            subscription.failures = 0;
+            subscription.retry_at = 0;
+            return;
+        }
+
+        subscription.failures += 1;
+        if subscription.failures >= MAX_DELIVERY_FAILURES {
+            self.subscriptions.remove(&id);
+            return;
+        }
+
+        subscription.retry_at = current_time + BASE_RETRY_DELAY_NS * (1 << (subscription.failures - 1));
+    }
+}
+
+pub struct SubscriptionRegistry;
+
+impl SubscriptionRegistry {
+    pub fn subscribe(
+        subscriber: Principal,
+        method: String,
+        filter: SubscriptionFilter,
+    ) -> Result<u64, LightClientError> {
+        SUBSCRIPTIONS.with_borrow_mut(|subscriptions| subscriptions.subscribe(subscriber, method, filter))
+    }
+
+    pub fn unsubscribe(id: u64) {
+        SUBSCRIPTIONS.with_borrow_mut(|subscriptions| subscriptions.subscriptions.remove(&id));
+    }
+
+    pub fn get(id: u64) -> Option<Subscription> {
+        SUBSCRIPTIONS.with_borrow(|subscriptions| subscriptions.subscriptions.get(&id).cloned())
+    }
+
+    pub fn list(subscriber: Principal) -> Vec<Subscription> {
+        SUBSCRIPTIONS.with_borrow(|subscriptions| {
+            subscriptions
+                .subscriptions
+                .values()
+                .filter(|s| s.subscriber == subscriber)
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn subscriptions() -> Subscriptions {
+        SUBSCRIPTIONS.with_borrow(|subscriptions| subscriptions.clone())
+    }
+
+    pub fn restore(subscriptions: Subscriptions) {
+        SUBSCRIPTIONS.set(subscriptions);
+    }
+
+    /// Queues the latest headers of `chain` for its subscribers.
+    pub fn publish(uid: ChainId, chain: &dyn Chain) {
+        for commitment in [Commitment::Optimistic, Commitment::Finalized] {
+            if let Ok(header) = chain.get_latest_block(commitment) {
+                SUBSCRIPTIONS.with_borrow_mut(|subscriptions| subscriptions.publish(uid, &header));
+            }
+        }
+    }
+
+    /// Sends a bounded number of due notifications. Notifications are one-way, so only failures to
+    /// enqueue them are observed, but an unresponsive subscriber can't block canister upgrades.
+    pub fn dispatch() {
+        let current_time = ic_cdk::api::time();
+        let due =
+            SUBSCRIPTIONS.with_borrow(|subscriptions| subscriptions.due(current_time, MAX_NOTIFICATIONS_PER_DISPATCH));
+
+        for (subscriber, method, notification) in due {
+            let id = notification.subscription_id;
+            let delivered = notify(subscriber, &method, (notification,)).is_ok();
+
+            SUBSCRIPTIONS.with_borrow_mut(|subscriptions| subscriptions.record_delivery(id, delivered, current_time));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubscriptionFilter, Subscriptions, MAX_DELIVERY_FAILURES, MAX_SUBSCRIPTIONS_PER_SUBSCRIBER};
+    use crate::types::{BlockHeader, Commitment};
+    use candid::Principal;
+    use ic_lightclient_wire::ChainId;
+
+    fn header(execution_block_number: u64, commitment: Commitment) -> BlockHeader {
+        BlockHeader {
+            commitment,
+            beacon_slot: execution_block_number,
+            beacon_block_root: String::new(),
+            execution_block_number,
+            execution_block_hash: String::new(),
+            timestamp: 0,
+            gas_limit: 0,
+            gas_used: 0,
+            base_fee_per_gas: 0,
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+        }
+    }
+
+    #[test]
+    fn test_subscription_delivery() {
+        let subscriber = Principal::from_slice(&[1]);
+        let filter = SubscriptionFilter { chain: ChainId(1), commitment: Commitment::Finalized, every_n_blocks: 4 };
+        let mut subscriptions = Subscriptions::default();
+        let id = subscriptions.subscribe(subscriber, "on_header".into(), filter).unwrap();
+
+        subscriptions.publish(ChainId(1), &header(100, Commitment::Optimistic));
+        subscriptions.publish(ChainId(2), &header(100, Commitment::Finalized));
+        assert!(subscriptions.due(0, 10).is_empty());
+
+        subscriptions.publish(ChainId(1), &header(100, Commitment::Finalized));
+        assert_eq!(subscriptions.due(0, 10)[0].2.header.execution_block_number, 100);
+        subscriptions.record_delivery(id, true, 0);
+
+        subscriptions.publish(ChainId(1), &header(103, Commitment::Finalized));
+        assert!(subscriptions.due(0, 10).is_empty());
+
+        subscriptions.publish(ChainId(1), &header(104, Commitment::Finalized));
+        subscriptions.record_delivery(id, false, 0);
+        assert!(subscriptions.due(0, 10).is_empty());
+        assert_eq!(subscriptions.due(u64::MAX, 10).len(), 1);
+
+        for _ in 1..MAX_DELIVERY_FAILURES {
+            subscriptions.record_delivery(id, false, 0);
+        }
+        assert!(subscriptions.subscriptions.is_empty());
+    }
+
+    #[test]
+    fn test_subscriber_limit() {
+        let filter = SubscriptionFilter { chain: ChainId(1), commitment: Commitment::Finalized, every_n_blocks: 1 };
+        let mut subscriptions = Subscriptions::default();
+
+        for _ in 0..MAX_SUBSCRIPTIONS_PER_SUBSCRIBER {
+            subscriptions
+                .subscribe(Principal::from_slice(&[1]), "on_header".into(), filter.clone())
+                .unwrap();
+        }
+        assert!(subscriptions
+            .subscribe(Principal::from_slice(&[1]), "on_header".into(), filter.clone())
+            .is_err());
+        assert!(subscriptions
+            .subscribe(Principal::from_slice(&[2]), "on_header".into(), filter)
+            .is_ok());
+    }
+}
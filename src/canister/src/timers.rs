@@ -0,0 +1,49 @@
+use crate::{state::GlobalState, subscriptions::SubscriptionRegistry};
+use anyhow::Result;
+use ic_lightclient_wire::ChainId;
+
+/// Periodic per-chain maintenance run by canister timers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainTask {
+    /// Moves finality forward without a sync committee quorum once it has stalled for more than
+    /// a sync committee period.
+    ForceUpdate,
+}
+
+pub struct TimerScheduler;
+
+impl TimerScheduler {
+    /// Arms the timers of all chains. Timers don't survive upgrades, so this runs after the chains
+    /// are initialized or restored.
+    pub fn start() -> Result<()> {
+        let state = GlobalState::state()?;
+        let state = state.borrow();
+
+        for (uid, chain) in state.chains.iter() {
+            for (task, interval) in chain.get_tasks() {
+                let uid = *uid;
+                ic_cdk_timers::set_timer_interval(interval, move || {
+                    if let Err(err) = Self::run(uid, task) {
+                        ic_cdk::println!("Task {:?} of chain {} failed: {:#}", task, uid, err);
+                    }
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn run(uid: ChainId, task: ChainTask) -> Result<()> {
+        let state = GlobalState::state()?;
+        let mut state = state.borrow_mut();
+        let chain = state.chain_mut(uid)?;
+
+        if !chain.run_task(task)? {
+            return Ok(());
+        }
+
+        SubscriptionRegistry::publish(uid, chain);
+        SubscriptionRegistry::dispatch();
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use crate::error::LightClientError;
+use candid::{CandidType, Nat};
+use ic_lightclient_ethereum::helios::types::LightClientHeader;
+use ic_lightclient_wire::ChainId;
+use serde::Deserialize;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Commitment {
+    Optimistic,
+    Finalized,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BlockHeader {
+    pub commitment: Commitment,
+    pub beacon_slot: u64,
+    pub beacon_block_root: String,
+    pub execution_block_number: u64,
+    pub execution_block_hash: String,
+    pub timestamp: u64,
+    pub gas_limit: u64,
+    pub gas_used: u64,
+    pub base_fee_per_gas: u128,
+    pub blob_gas_used: u64,
+    pub excess_blob_gas: u64,
+}
+
+impl BlockHeader {
+    pub fn from_light_client_header(header: &LightClientHeader, commitment: Commitment) -> Self {
+        let execution = &header.execution;
+
+        Self {
+            commitment,
+            beacon_slot: header.beacon.slot,
+            beacon_block_root: header.beacon_block_root().to_string(),
+            execution_block_number: execution.block_number,
+            execution_block_hash: execution.block_hash.to_string(),
+            timestamp: execution.timestamp,
+            gas_limit: execution.gas_limit,
+            gas_used: execution.gas_used,
+            base_fee_per_gas: execution.base_fee_per_gas.saturating_to(),
+            blob_gas_used: execution.blob_gas_used,
+            excess_blob_gas: execution.excess_blob_gas,
+        }
+    }
+}
+
+/// Lookup key of a header in the verified header history.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum HeaderQuery {
+    Slot(u64),
+    Number(u64),
+    Hash(String),
+}
+
+/// Account state proven against the execution state root of `block_number`.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Account {
+    pub address: String,
+    pub block_number: u64,
+    pub state_root: String,
+    pub nonce: u64,
+    pub balance: Nat,
+    pub storage_hash: String,
+    pub code_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StorageSlot {
+    pub address: String,
+    pub slot: String,
+    pub block_number: u64,
+    pub value: String,
+}
+
+/// Event log of a receipt proven against the execution receipts root of its block.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Log {
+    pub block_number: u64,
+    pub block_hash: String,
+    pub transaction_index: u64,
+    /// Position of the log within its receipt.
+    pub log_index: u64,
+    /// Commitment of the block, logs of optimistic blocks are dropped if the block gets reorged out.
+    pub commitment: Commitment,
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// Selects logs like `eth_getLogs`, a `None` topic matches any topic at its position.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LogQuery {
+    pub address: Option<String>,
+    pub topics: Vec<Option<String>>,
+    pub from_block: Option<u64>,
+}
+
+/// Transaction with its proof against the execution header of its block, see
+/// `ic_lightclient_ethereum::execution::TransactionProof`.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransactionProof {
+    pub block_number: u64,
+    pub index: u64,
+    /// RLP encoded execution header.
+    pub header: Vec<u8>,
+    /// EIP-2718 encoded transaction.
+    pub transaction: Vec<u8>,
+    pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionType {
+    Legacy,
+    Eip2930,
+    Eip1559,
+    Eip4844,
+    Eip7702,
+}
+
+/// Transaction proven to be included in the block at `block_number`.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub hash: String,
+    pub block_number: u64,
+    pub block_hash: String,
+    pub index: u64,
+    pub tx_type: TransactionType,
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    /// `None` for contract creations.
+    pub to: Option<String>,
+    pub value: Nat,
+    pub input: String,
+    pub gas_limit: u64,
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Freshness {
+    /// IC time in nanoseconds of the last accepted update.
+    pub last_updated: Option<u64>,
+    /// Chain reported timestamp in seconds of the served data.
+    pub chain_timestamp: Option<u64>,
+    /// Age in seconds of the served data, based on the chain timestamp when it is known.
+    pub age: Option<u64>,
+    pub freshness_sla: Option<u64>,
+}
+
+impl Freshness {
+    pub fn new(
+        last_updated: Option<u64>,
+        chain_timestamp: Option<u64>,
+        freshness_sla: Option<u64>,
+        current_time: u64,
+    ) -> Self {
+        let current_time_sec = current_time / 1_000_000_000;
+        let updated_at_sec = chain_timestamp.or(last_updated.map(|time| time / 1_000_000_000));
+        let age = updated_at_sec.map(|updated_at| current_time_sec.saturating_sub(updated_at));
+
+        Self { last_updated, chain_timestamp, age, freshness_sla }
+    }
+
+    pub fn is_within(&self, max_age: u64) -> bool {
+        self.age.is_some_and(|age| age <= max_age)
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RejectedUpdate {
+    /// Position of the update within the chain's updates in the payload.
+    pub index: u64,
+    pub error: LightClientError,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ChainUpdateReport {
+    pub chain: ChainId,
+    pub applied: u64,
+    /// Updates accepted but waiting on other agents before they're applied.
+    pub pending: u64,
+    pub rejected: Vec<RejectedUpdate>,
+    pub optimistic_slot: Option<u64>,
+    pub finalized_slot: Option<u64>,
+}
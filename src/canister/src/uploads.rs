@@ -0,0 +1,144 @@
+use crate::error::LightClientError;
+use candid::Principal;
+use ic_lightclient_wire::UploadSession;
+use std::{cell::RefCell, collections::BTreeMap};
+
+const MAX_UPLOAD_SESSIONS: usize = 16;
+/// Agents run an upload per loop (e.g. the main one and a backfill) and close failed ones by committing them.
+const MAX_UPLOAD_SESSIONS_PER_CALLER: usize = 2;
+/// The reassembled payload is parsed and applied within the single `commit_upload` message, which
+/// a few times the ingress limit already takes a good part of the instruction limit for.
+const MAX_UPLOAD_LEN: u64 = 8 * 1024 * 1024;
+const UPLOAD_TIMEOUT_NS: u64 = 600_000_000_000;
+
+thread_local! {
+    static UPLOADS: RefCell<Uploads> = RefCell::new(Uploads::default());
+}
+
+struct PendingUpload {
+    session: UploadSession,
+    started_at: u64,
+}
+
+/// Upload sessions in progress, keyed by the caller and its session id. They only live on the
+/// heap, so an upgrade drops them and the agent starts over.
+#[derive(Default)]
+struct Uploads {
+    sessions: BTreeMap<(Principal, u64), PendingUpload>,
+}
+
+impl Uploads {
+    fn begin(
+        &mut self,
+        caller: Principal,
+        session: u64,
+        total_len: u64,
+        sha256: Vec<u8>,
+        current_time: u64,
+    ) -> Result<(), LightClientError> {
+        if total_len > MAX_UPLOAD_LEN {
+            let message = format!("Upload of {} bytes exceeds {} bytes.", total_len, MAX_UPLOAD_LEN);
+            return Err(LightClientError::InvalidArgument(message));
+        }
+
+        self.sessions
+            .retain(|_, upload| upload.started_at + UPLOAD_TIMEOUT_NS > current_time);
+        let key = (caller, session);
+        let owned = self.sessions.keys().filter(|(owner, _)| *owner == caller).count();
+        if !self.sessions.contains_key(&key)
+            && (owned >= MAX_UPLOAD_SESSIONS_PER_CALLER || self.sessions.len() >= MAX_UPLOAD_SESSIONS)
+        {
+            return Err(LightClientError::LimitExceeded("upload sessions".into()));
+        }
+
+        let session =
+            UploadSession::new(total_len, sha256).map_err(|e| LightClientError::InvalidArgument(e.to_string()))?;
+        self.sessions.insert(key, PendingUpload { session, started_at: current_time });
+
+        Ok(())
+    }
+
+    fn append(&mut self, caller: Principal, session: u64, chunk: &[u8]) -> Result<u64, LightClientError> {
+        let upload = self
+            .sessions
+            .get_mut(&(caller, session))
+            .ok_or(LightClientError::NotFound(format!("Upload session {}.", session)))?;
+
+        upload
+            .session
+            .append(chunk)
+            .map_err(|e| LightClientError::InvalidPayload(e.to_string()))
+    }
+
+    /// The session is closed whether or not the upload turns out to be complete.
+    fn commit(&mut self, caller: Principal, session: u64) -> Result<Vec<u8>, LightClientError> {
+        let upload = self
+            .sessions
+            .remove(&(caller, session))
+            .ok_or(LightClientError::NotFound(format!("Upload session {}.", session)))?;
+
+        upload
+            .session
+            .finish()
+            .map_err(|e| LightClientError::InvalidPayload(e.to_string()))
+    }
+}
+
+pub struct UploadRegistry;
+
+impl UploadRegistry {
+    pub fn begin(caller: Principal, session: u64, total_len: u64, sha256: Vec<u8>) -> Result<(), LightClientError> {
+        let current_time = ic_cdk::api::time();
+        UPLOADS.with_borrow_mut(|uploads| uploads.begin(caller, session, total_len, sha256, current_time))
+    }
+
+    pub fn append(caller: Principal, session: u64, chunk: &[u8]) -> Result<u64, LightClientError> {
+        UPLOADS.with_borrow_mut(|uploads| uploads.append(caller, session, chunk))
+    }
+
+    /// Returns the reassembled payload once its length and hash match the announced ones.
+    pub fn commit(caller: Principal, session: u64) -> Result<Vec<u8>, LightClientError> {
+        UPLOADS.with_borrow_mut(|uploads| uploads.commit(caller, session))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Uploads, MAX_UPLOAD_SESSIONS, MAX_UPLOAD_SESSIONS_PER_CALLER, UPLOAD_TIMEOUT_NS};
+    use candid::Principal;
+    use ic_lightclient_wire::ChunkedUpload;
+
+    #[test]
+    fn test_uploads() {
+        let caller = Principal::anonymous();
+        let payload = vec![7u8; 100];
+        let upload = ChunkedUpload::new(&payload, 40);
+        let mut uploads = Uploads::default();
+
+        uploads.begin(caller, 1, upload.total_len, upload.sha256.clone(), 0).unwrap();
+        for chunk in &upload.chunks {
+            uploads.append(caller, 1, chunk).unwrap();
+        }
+        assert!(uploads.append(Principal::management_canister(), 1, &upload.chunks[0]).is_err());
+        assert_eq!(uploads.commit(caller, 1).unwrap(), payload);
+        assert!(uploads.commit(caller, 1).is_err());
+
+        for session in 0..MAX_UPLOAD_SESSIONS_PER_CALLER as u64 {
+            uploads.begin(caller, session, 1, upload.sha256.clone(), 0).unwrap();
+        }
+        assert!(uploads.begin(caller, 100, 1, upload.sha256.clone(), 0).is_err());
+        uploads.begin(caller, 0, 1, upload.sha256.clone(), 0).unwrap();
+
+        let callers: Vec<Principal> = (0..MAX_UPLOAD_SESSIONS as u8)
+            .map(|i| Principal::from_slice(&[0xff, i]))
+            .collect();
+        for caller in &callers[..MAX_UPLOAD_SESSIONS - MAX_UPLOAD_SESSIONS_PER_CALLER] {
+            uploads.begin(*caller, 1, 1, upload.sha256.clone(), 0).unwrap();
+        }
+        assert!(uploads
+            .begin(callers[MAX_UPLOAD_SESSIONS - 1], 1, 1, upload.sha256.clone(), 1)
+            .is_err());
+        uploads.begin(caller, 100, 1, upload.sha256.clone(), UPLOAD_TIMEOUT_NS).unwrap();
+        assert_eq!(uploads.sessions.len(), 1);
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{checkpoint::EthereumCheckpoint, helios::types::Forks};
+use crate::{
+    checkpoint::EthereumCheckpoint,
+    execution::{LogFilter, WatchedAccount},
+    helios::types::Forks,
+};
 use alloy_primitives::B256;
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +14,24 @@ pub struct EthereumConfig {
     pub genesis_validator_root: B256,
     pub genesis_time: u64,
     pub forks: Forks,
+    /// Maximum age in seconds of the optimistic header before the chain is reported as stale.
+    #[serde(default)]
+    pub freshness_sla: Option<u64>,
+    /// Interval in seconds of the canister task forcing stalled finality forward, 0 disables it.
+    #[serde(default = "default_force_update_interval")]
+    pub force_update_interval: u64,
+    /// Accounts whose state is proven against the verified execution state root.
+    #[serde(default)]
+    pub watched_accounts: Vec<WatchedAccount>,
+    /// Event logs proven against the verified execution receipts root.
+    #[serde(default)]
+    pub watched_logs: Vec<LogFilter>,
+    /// Number of recently verified headers kept for lookups and proof verification.
+    #[serde(default = "default_header_history_depth")]
+    pub header_history_depth: usize,
+    /// Number of backfilled execution block hashes kept for canonical chain lookups.
+    #[serde(default = "default_canonical_hashes_depth")]
+    pub canonical_hashes_depth: usize,
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +43,30 @@ pub struct EthereumConfigPopulated {
     pub genesis_time: u64,
     pub forks: Forks,
     pub checkpoint: EthereumCheckpoint,
+    #[serde(default)]
+    pub freshness_sla: Option<u64>,
+    #[serde(default = "default_force_update_interval")]
+    pub force_update_interval: u64,
+    #[serde(default)]
+    pub watched_accounts: Vec<WatchedAccount>,
+    #[serde(default)]
+    pub watched_logs: Vec<LogFilter>,
+    #[serde(default = "default_header_history_depth")]
+    pub header_history_depth: usize,
+    #[serde(default = "default_canonical_hashes_depth")]
+    pub canonical_hashes_depth: usize,
+}
+
+fn default_force_update_interval() -> u64 {
+    384
+}
+
+fn default_header_history_depth() -> usize {
+    256
+}
+
+fn default_canonical_hashes_depth() -> usize {
+    8192
 }
 
 impl EthereumConfig {
@@ -33,6 +79,12 @@ impl EthereumConfig {
             genesis_time: self.genesis_time,
             forks: self.forks,
             checkpoint,
+            freshness_sla: self.freshness_sla,
+            force_update_interval: self.force_update_interval,
+            watched_accounts: self.watched_accounts,
+            watched_logs: self.watched_logs,
+            header_history_depth: self.header_history_depth,
+            canonical_hashes_depth: self.canonical_hashes_depth,
         }
     }
 }
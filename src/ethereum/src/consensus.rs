@@ -2,10 +2,13 @@ use crate::{
     config::EthereumConfigPopulated,
     helios::{
         consensus::{
-            apply_bootstrap, apply_generic_update, expected_current_slot, verify_bootstrap, verify_generic_update,
+            apply_bootstrap, apply_generic_update, expected_current_slot, force_update, verify_ancestry,
+            verify_bootstrap, verify_generic_update,
         },
+        errors::ConsensusError,
+        proof::AncestryProof,
         spec::ConsensusSpec,
-        types::{Bootstrap, GenericUpdate, LightClientStore},
+        types::{BeaconBlockHeader, Bootstrap, GenericUpdate, LightClientHeader, LightClientStore},
     },
     payload::{diff_store, patch_store, LightClientState, LightClientStoreDiff},
 };
@@ -24,6 +27,24 @@ impl<S: ConsensusSpec> EthereumLightClientConsensus<S> {
         Self { is_bootstrapped: false, store: LightClientStore::default(), config }
     }
 
+    pub fn load_state(&mut self, state: LightClientState<S>) {
+        match state {
+            LightClientState::Bootstrap(_) => {
+                self.store = LightClientStore::default();
+                self.is_bootstrapped = false;
+            }
+
+            LightClientState::Active(store) => {
+                self.store = store;
+                self.is_bootstrapped = true;
+            }
+        }
+    }
+
+    pub fn is_bootstrapped(&self) -> bool {
+        self.is_bootstrapped
+    }
+
     pub fn get_state(&self) -> Result<LightClientState<S>> {
         if !self.is_bootstrapped {
             let checkpoint = self.config.checkpoint.checkpoint_block_root;
@@ -43,7 +64,7 @@ impl<S: ConsensusSpec> EthereumLightClientConsensus<S> {
         let checkpoint = config.checkpoint.checkpoint_block_root;
         let forks = &config.forks;
 
-        verify_bootstrap(bootstrap, checkpoint, forks).map_err(|e| anyhow!(e))?;
+        verify_bootstrap(bootstrap, checkpoint, forks).map_err(into_anyhow)?;
 
         apply_bootstrap(&mut self.store, bootstrap);
         self.is_bootstrapped = true;
@@ -52,19 +73,43 @@ impl<S: ConsensusSpec> EthereumLightClientConsensus<S> {
     }
 
     pub fn update(&mut self, update: &GenericUpdate<S>, current_time: u64) -> Result<()> {
+        if !self.is_bootstrapped {
+            return Err(anyhow!("Received update before being bootstrapped."));
+        }
+
         let config = &self.config;
         let genesis_root = config.genesis_validator_root;
         let genesis_time = config.genesis_time;
         let forks = &config.forks;
-        let current_slot = expected_current_slot(current_time, genesis_time);
+        let current_slot = expected_current_slot::<S>(current_time, genesis_time);
 
-        verify_generic_update(update, current_slot, &self.store, genesis_root, forks).map_err(|e| anyhow!(e))?;
+        verify_generic_update(update, current_slot, &self.store, genesis_root, forks).map_err(into_anyhow)?;
 
         apply_generic_update(&mut self.store, update);
 
         Ok(())
     }
 
+    /// Verifies a header older than the store against the finalized `anchor`, which the caller
+    /// has to have verified.
+    pub fn verify_ancestry(&self, proof: &AncestryProof, anchor: &BeaconBlockHeader) -> Result<()> {
+        verify_ancestry::<S>(proof, anchor, &self.config.forks).map_err(into_anyhow)
+    }
+
+    /// Applies the best valid update without a sync committee quorum once finality has stalled for
+    /// more than a sync committee period. Returns whether the store changed.
+    pub fn force_update(&mut self, current_time: u64) -> bool {
+        let Some(best_valid_update) = &self.store.best_valid_update else { return false };
+        if !self.is_bootstrapped || best_valid_update.finalized_header.is_none() {
+            return false;
+        }
+
+        let current_slot = expected_current_slot::<S>(current_time, self.config.genesis_time);
+        force_update(&mut self.store, current_slot);
+
+        self.store.best_valid_update.is_none()
+    }
+
     pub fn diff(&self, store: &LightClientStore<S>) -> Option<LightClientStoreDiff<S>> {
         diff_store(&self.store, store)
     }
@@ -77,14 +122,26 @@ impl<S: ConsensusSpec> EthereumLightClientConsensus<S> {
         if !self.is_bootstrapped {
             self.config.checkpoint.checkpoint_block_root.to_string()
         } else {
-            format!(
-                "Slot: {}, hash: {}",
-                self.store.optimistic_header.beacon.slot,
-                self.store.optimistic_header.beacon.state_root.to_string()
-            )
+            self.store.optimistic_header.execution.block_hash.to_string()
         }
     }
 
+    pub fn get_optimistic_header(&self) -> Option<&LightClientHeader> {
+        self.is_bootstrapped.then_some(&self.store.optimistic_header)
+    }
+
+    pub fn get_finalized_header(&self) -> Option<&LightClientHeader> {
+        self.is_bootstrapped.then_some(&self.store.finalized_header)
+    }
+
+    pub fn get_config(&self) -> &EthereumConfigPopulated {
+        &self.config
+    }
+
+    pub fn get_store(&self) -> &LightClientStore<S> {
+        &self.store
+    }
+
     pub fn get_checkpoint_root(&self) -> B256 {
         self.config.checkpoint.checkpoint_block_root
     }
@@ -101,3 +158,11 @@ impl<S: ConsensusSpec> EthereumLightClientConsensus<S> {
         self.store.next_sync_committee.is_some()
     }
 }
+
+/// Keeps verification failures as `ConsensusError` so callers can downcast and report them.
+fn into_anyhow(err: eyre::Report) -> anyhow::Error {
+    match err.downcast::<ConsensusError>() {
+        Ok(err) => err.into(),
+        Err(err) => anyhow!(err),
+    }
+}
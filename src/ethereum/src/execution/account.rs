@@ -0,0 +1,130 @@
+use crate::execution::mpt::{verify_proof, ProofError};
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_trie::{TrieAccount, EMPTY_ROOT_HASH, KECCAK_EMPTY};
+use serde::{Deserialize, Serialize};
+
+/// Account the agent keeps proving, along with the storage slots to prove.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct WatchedAccount {
+    pub address: Address,
+    #[serde(default)]
+    pub storage_slots: Vec<B256>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StorageProof {
+    pub slot: B256,
+    pub value: U256,
+    pub proof: Vec<Bytes>,
+}
+
+/// `eth_getProof` response for an account at `block_number`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountProof {
+    pub block_number: u64,
+    pub address: Address,
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_hash: B256,
+    pub code_hash: B256,
+    pub account_proof: Vec<Bytes>,
+    pub storage_proof: Vec<StorageProof>,
+}
+
+impl AccountProof {
+    /// Nodes differ in how they report the hashes of accounts which don't exist.
+    fn is_empty(&self) -> bool {
+        self.nonce == 0
+            && self.balance.is_zero()
+            && (self.storage_hash.is_zero() || self.storage_hash == EMPTY_ROOT_HASH)
+            && (self.code_hash.is_zero() || self.code_hash == KECCAK_EMPTY)
+    }
+
+    /// Verifies the account against `state_root` and its storage slots against the proven
+    /// storage root.
+    pub fn verify(&self, state_root: B256) -> Result<(), ProofError> {
+        let account = (!self.is_empty()).then(|| {
+            alloy_rlp::encode(TrieAccount {
+                nonce: self.nonce,
+                balance: self.balance,
+                storage_root: self.storage_hash,
+                code_hash: self.code_hash,
+            })
+        });
+
+        if !verify_proof(state_root, keccak256(self.address).as_slice(), account, &self.account_proof) {
+            return Err(ProofError::InvalidAccountProof(self.address));
+        }
+
+        let storage_root = if self.is_empty() { EMPTY_ROOT_HASH } else { self.storage_hash };
+        for storage in &self.storage_proof {
+            let value = (!storage.value.is_zero()).then(|| alloy_rlp::encode(storage.value));
+            if !verify_proof(storage_root, keccak256(storage.slot).as_slice(), value, &storage.proof) {
+                return Err(ProofError::InvalidStorageProof { address: self.address, slot: storage.slot });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountProof, StorageProof};
+    use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+    use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles, TrieAccount, KECCAK_EMPTY};
+
+    /// Builds a trie over the given hashed keys and returns its root and the proof of `target`.
+    fn prove(mut leaves: Vec<(B256, Vec<u8>)>, target: B256) -> (B256, Vec<Bytes>) {
+        leaves.sort();
+        let target = Nibbles::unpack(target);
+        let mut builder = HashBuilder::default().with_proof_retainer(ProofRetainer::new(vec![target.clone()]));
+        for (key, value) in leaves {
+            builder.add_leaf(Nibbles::unpack(key), &value);
+        }
+
+        let root = builder.root();
+        let proof = builder.take_proof_nodes().matching_nodes_sorted(&target);
+        (root, proof.into_iter().map(|(_, node)| node).collect())
+    }
+
+    #[test]
+    fn test_account_proof() {
+        let slot = B256::with_last_byte(1);
+        let value = U256::from(42);
+        let storage_leaves = vec![
+            (keccak256(slot), alloy_rlp::encode(value)),
+            (keccak256(B256::with_last_byte(2)), alloy_rlp::encode(U256::from(7))),
+        ];
+        let (storage_hash, storage_proof) = prove(storage_leaves, keccak256(slot));
+
+        let address = Address::with_last_byte(1);
+        let account =
+            TrieAccount { nonce: 1, balance: U256::from(100), storage_root: storage_hash, code_hash: KECCAK_EMPTY };
+        let other = TrieAccount { nonce: 5, ..TrieAccount::default() };
+        let state_leaves = vec![
+            (keccak256(address), alloy_rlp::encode(account)),
+            (keccak256(Address::with_last_byte(2)), alloy_rlp::encode(other)),
+        ];
+        let (state_root, account_proof) = prove(state_leaves, keccak256(address));
+
+        let mut proof = AccountProof {
+            block_number: 1,
+            address,
+            nonce: 1,
+            balance: U256::from(100),
+            storage_hash,
+            code_hash: KECCAK_EMPTY,
+            account_proof,
+            storage_proof: vec![StorageProof { slot, value, proof: storage_proof }],
+        };
+        assert!(proof.verify(state_root).is_ok());
+
+        proof.storage_proof[0].value = U256::from(43);
+        assert!(proof.verify(state_root).is_err());
+
+        proof.storage_proof[0].value = value;
+        proof.balance = U256::from(101);
+        assert!(proof.verify(state_root).is_err());
+    }
+}
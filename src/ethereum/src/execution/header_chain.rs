@@ -0,0 +1,85 @@
+use crate::execution::mpt::ProofError;
+use alloy_consensus::Header;
+use alloy_primitives::{keccak256, Bytes, B256};
+use alloy_rlp::Decodable;
+use serde::{Deserialize, Serialize};
+
+/// Contiguous run of RLP encoded execution headers, oldest first. The run is anchored by the hash
+/// of its last header, every other header is then verified by the `parent_hash` of its child.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct HeaderChain {
+    pub headers: Vec<Bytes>,
+}
+
+impl HeaderChain {
+    pub fn build(headers: &[Header]) -> Self {
+        Self { headers: headers.iter().map(|header| alloy_rlp::encode(header).into()).collect() }
+    }
+
+    /// Verifies the run backwards from its last header, which has to hash to `block_hash`, and
+    /// returns the number and hash of every header, oldest first.
+    pub fn verify(&self, block_hash: B256) -> Result<Vec<(u64, B256)>, ProofError> {
+        let mut verified = Vec::with_capacity(self.headers.len());
+        let mut expected_hash = block_hash;
+        let mut expected_number = None;
+
+        for encoded in self.headers.iter().rev() {
+            let header = Header::decode(&mut encoded.as_ref())
+                .map_err(|_| ProofError::InvalidHeader(expected_number.unwrap_or_default()))?;
+            if keccak256(encoded) != expected_hash {
+                return Err(ProofError::InvalidHeader(header.number));
+            }
+            if expected_number.is_some_and(|number| number != header.number) {
+                return Err(ProofError::BrokenHeaderChain(header.number));
+            }
+
+            verified.push((header.number, expected_hash));
+            expected_hash = header.parent_hash;
+            expected_number = header.number.checked_sub(1);
+        }
+
+        verified.reverse();
+        Ok(verified)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HeaderChain;
+    use alloy_consensus::Header;
+    use alloy_primitives::B256;
+
+    fn headers(count: u64) -> Vec<Header> {
+        let mut parent_hash = B256::repeat_byte(1);
+        (100..100 + count)
+            .map(|number| {
+                let header = Header { number, parent_hash, ..Default::default() };
+                parent_hash = header.hash_slow();
+                header
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_header_chain() {
+        let headers = headers(16);
+        let block_hash = headers.last().unwrap().hash_slow();
+
+        let verified = HeaderChain::build(&headers).verify(block_hash).unwrap();
+        assert_eq!(verified.len(), headers.len());
+        for ((number, hash), header) in verified.iter().zip(&headers) {
+            assert_eq!(*number, header.number);
+            assert_eq!(*hash, header.hash_slow());
+        }
+
+        assert!(HeaderChain::build(&headers).verify(B256::ZERO).is_err());
+
+        let mut broken = headers.clone();
+        broken.remove(8);
+        assert!(HeaderChain::build(&broken).verify(block_hash).is_err());
+
+        let mut tampered = headers.clone();
+        tampered[4].gas_used = 1;
+        assert!(HeaderChain::build(&tampered).verify(block_hash).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+mod account;
+mod header_chain;
+mod mpt;
+mod receipt;
+mod transaction;
+
+pub use account::{AccountProof, StorageProof, WatchedAccount};
+pub use header_chain::HeaderChain;
+pub use mpt::{prove_ordered_trie, verify_proof, ProofError};
+pub use receipt::{LogFilter, ReceiptProof};
+pub use transaction::TransactionProof;
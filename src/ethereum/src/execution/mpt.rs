@@ -0,0 +1,56 @@
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_trie::{proof::verify_proof as verify_trie_proof, proof::ProofRetainer, HashBuilder, Nibbles};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("invalid account proof for {0}")]
+    InvalidAccountProof(Address),
+    #[error("invalid storage proof for slot {slot} of {address}")]
+    InvalidStorageProof { address: Address, slot: B256 },
+    #[error("invalid proof for receipt {0}")]
+    InvalidReceiptProof(u64),
+    #[error("receipt {0} can't be decoded")]
+    InvalidReceipt(u64),
+    #[error("execution header of block {0} doesn't match the verified block hash")]
+    InvalidHeader(u64),
+    #[error("execution header of block {0} doesn't link to its child")]
+    BrokenHeaderChain(u64),
+    #[error("invalid proof for transaction {0}")]
+    InvalidTransactionProof(u64),
+    #[error("transaction {0} can't be decoded")]
+    InvalidTransaction(u64),
+}
+
+/// Verifies a Merkle-Patricia proof that `key` maps to the RLP encoded `value` in the trie with
+/// root `root`. A `None` value verifies that `key` is not in the trie.
+pub fn verify_proof(root: B256, key: &[u8], value: Option<Vec<u8>>, proof: &[Bytes]) -> bool {
+    verify_trie_proof(root, Nibbles::unpack(key), value, proof).is_ok()
+}
+
+/// Builds the trie of a block's list, keyed by the RLP encoded index of each item as the
+/// transactions and receipts tries are, and returns its root with the proofs of `indices`.
+pub fn prove_ordered_trie(items: &[Bytes], indices: &[u64]) -> (B256, Vec<Vec<Bytes>>) {
+    let targets: Vec<Nibbles> = indices.iter().map(|index| Nibbles::unpack(alloy_rlp::encode(index))).collect();
+
+    let mut leaves: Vec<(Nibbles, &Bytes)> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| (Nibbles::unpack(alloy_rlp::encode(index as u64)), item))
+        .collect();
+    leaves.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut builder = HashBuilder::default().with_proof_retainer(ProofRetainer::new(targets.clone()));
+    for (key, item) in leaves {
+        builder.add_leaf(key, item);
+    }
+
+    let root = builder.root();
+    let nodes = builder.take_proof_nodes();
+    let proofs = targets
+        .iter()
+        .map(|target| nodes.matching_nodes_sorted(target).into_iter().map(|(_, node)| node).collect())
+        .collect();
+
+    (root, proofs)
+}
@@ -0,0 +1,91 @@
+use crate::execution::mpt::{prove_ordered_trie, verify_proof, ProofError};
+use alloy_consensus::ReceiptEnvelope;
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::{Address, Bytes, Log, B256};
+use serde::{Deserialize, Serialize};
+
+/// Logs the agent proves, matching `address` and, when set, any of the event signatures in
+/// `topics`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct LogFilter {
+    pub address: Address,
+    #[serde(default)]
+    pub topics: Vec<B256>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, log: &Log) -> bool {
+        log.address == self.address
+            && (self.topics.is_empty() || log.topics().first().is_some_and(|topic| self.topics.contains(topic)))
+    }
+}
+
+/// EIP-2718 encoded receipt at `index` of the block at `block_number`, with its proof against the
+/// block's receipts root.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReceiptProof {
+    pub block_number: u64,
+    pub index: u64,
+    pub receipt: Bytes,
+    pub proof: Vec<Bytes>,
+}
+
+impl ReceiptProof {
+    /// Builds the proofs of the receipts at `indices` from all receipts of the block, returning
+    /// them with the receipts root they prove against.
+    pub fn build(block_number: u64, receipts: &[ReceiptEnvelope], indices: &[u64]) -> (B256, Vec<Self>) {
+        let encoded: Vec<Bytes> = receipts.iter().map(|receipt| receipt.encoded_2718().into()).collect();
+
+        let (root, proofs) = prove_ordered_trie(&encoded, indices);
+        let proofs = indices
+            .iter()
+            .zip(proofs)
+            .map(|(&index, proof)| Self { block_number, index, receipt: encoded[index as usize].clone(), proof })
+            .collect();
+
+        (root, proofs)
+    }
+
+    pub fn verify(&self, receipts_root: B256) -> Result<ReceiptEnvelope, ProofError> {
+        let key = alloy_rlp::encode(self.index);
+        if !verify_proof(receipts_root, &key, Some(self.receipt.to_vec()), &self.proof) {
+            return Err(ProofError::InvalidReceiptProof(self.index));
+        }
+
+        ReceiptEnvelope::decode_2718(&mut self.receipt.as_ref()).map_err(|_| ProofError::InvalidReceipt(self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogFilter, ReceiptProof};
+    use alloy_consensus::{proofs::calculate_receipt_root, Eip658Value, Receipt, ReceiptEnvelope};
+    use alloy_primitives::{Address, Bytes, Log, B256};
+
+    fn receipt(logs: Vec<Log>) -> ReceiptEnvelope {
+        let receipt = Receipt { status: Eip658Value::Eip658(true), cumulative_gas_used: 21000, logs };
+        ReceiptEnvelope::Eip1559(receipt.with_bloom())
+    }
+
+    #[test]
+    fn test_receipt_proof() {
+        let address = Address::with_last_byte(1);
+        let topic = B256::with_last_byte(1);
+        let log = Log::new_unchecked(address, vec![topic], Bytes::from_static(&[1]));
+        let receipts: Vec<ReceiptEnvelope> = (0..130)
+            .map(|i| if i == 129 { receipt(vec![log.clone()]) } else { receipt(vec![]) })
+            .collect();
+
+        let (root, proofs) = ReceiptProof::build(10, &receipts, &[0, 129]);
+        assert_eq!(root, calculate_receipt_root(&receipts));
+
+        let proven = proofs[1].verify(root).unwrap();
+        let filter = LogFilter { address, topics: vec![topic] };
+        assert!(proven.logs().iter().any(|log| filter.matches(log)));
+        assert!(proofs[0].verify(root).unwrap().logs().is_empty());
+
+        let mut forged = proofs[1].clone();
+        forged.index = 0;
+        assert!(forged.verify(root).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::execution::mpt::{prove_ordered_trie, verify_proof, ProofError};
+use alloy_consensus::{Header, TxEnvelope};
+use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+use alloy_primitives::{keccak256, Bytes, B256};
+use alloy_rlp::Decodable;
+use serde::{Deserialize, Serialize};
+
+/// EIP-2718 encoded transaction at `index` of the block at `block_number`, with its proof against
+/// the transactions trie. The consensus layer only commits to an SSZ root of the transactions, so
+/// the proof goes through the RLP encoded execution `header`, which the block hash commits to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransactionProof {
+    pub block_number: u64,
+    pub index: u64,
+    pub header: Bytes,
+    pub transaction: Bytes,
+    pub proof: Vec<Bytes>,
+}
+
+impl TransactionProof {
+    pub fn build(header: &Header, transactions: &[TxEnvelope], index: u64) -> Self {
+        let encoded: Vec<Bytes> = transactions.iter().map(|tx| tx.encoded_2718().into()).collect();
+        let (_, mut proofs) = prove_ordered_trie(&encoded, &[index]);
+
+        Self {
+            block_number: header.number,
+            index,
+            header: alloy_rlp::encode(header).into(),
+            transaction: encoded[index as usize].clone(),
+            proof: proofs.remove(0),
+        }
+    }
+
+    /// Verifies the transaction against the execution header of the block with hash `block_hash`.
+    pub fn verify(&self, block_hash: B256) -> Result<TxEnvelope, ProofError> {
+        if keccak256(&self.header) != block_hash {
+            return Err(ProofError::InvalidHeader(self.block_number));
+        }
+
+        let header =
+            Header::decode(&mut self.header.as_ref()).map_err(|_| ProofError::InvalidHeader(self.block_number))?;
+        if header.number != self.block_number {
+            return Err(ProofError::InvalidHeader(self.block_number));
+        }
+
+        let key = alloy_rlp::encode(self.index);
+        if !verify_proof(header.transactions_root, &key, Some(self.transaction.to_vec()), &self.proof) {
+            return Err(ProofError::InvalidTransactionProof(self.index));
+        }
+
+        TxEnvelope::decode_2718(&mut self.transaction.as_ref()).map_err(|_| ProofError::InvalidTransaction(self.index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransactionProof;
+    use alloy_consensus::{
+        proofs::calculate_transaction_root, Header, SignableTransaction, Transaction, TxEip1559, TxEnvelope, TxLegacy,
+    };
+    use alloy_primitives::{Signature, TxKind, B256, U256};
+
+    #[test]
+    fn test_transaction_proof() {
+        let signature = Signature::from_scalars_and_parity(B256::repeat_byte(1), B256::repeat_byte(2), false);
+        let transactions: Vec<TxEnvelope> = (0..20)
+            .map(|nonce| match nonce % 2 {
+                0 => TxLegacy { nonce, to: TxKind::Create, ..Default::default() }
+                    .into_signed(signature)
+                    .into(),
+                _ => TxEip1559 { chain_id: 1, nonce, value: U256::from(nonce), ..Default::default() }
+                    .into_signed(signature)
+                    .into(),
+            })
+            .collect();
+
+        let header =
+            Header { number: 100, transactions_root: calculate_transaction_root(&transactions), ..Default::default() };
+        let block_hash = header.hash_slow();
+
+        let proof = TransactionProof::build(&header, &transactions, 7);
+        let transaction = proof.verify(block_hash).unwrap();
+        assert_eq!(transaction.tx_hash(), transactions[7].tx_hash());
+        assert_eq!(transaction.nonce(), 7);
+
+        assert!(proof.verify(B256::ZERO).is_err());
+
+        let mut forged = proof.clone();
+        forged.transaction = TransactionProof::build(&header, &transactions, 8).transaction;
+        assert!(forged.verify(block_hash).is_err());
+    }
+}
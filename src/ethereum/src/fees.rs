@@ -0,0 +1,46 @@
+use crate::helios::types::ExecutionPayloadHeader;
+use alloy_primitives::U256;
+
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// Projects the base fee of the block following `parent` as specified by EIP-1559.
+pub fn next_base_fee_per_gas(parent: &ExecutionPayloadHeader) -> U256 {
+    let base_fee = parent.base_fee_per_gas;
+    let gas_target = parent.gas_limit / ELASTICITY_MULTIPLIER;
+
+    if gas_target == 0 || parent.gas_used == gas_target {
+        return base_fee;
+    }
+
+    let denominator = U256::from(gas_target) * U256::from(BASE_FEE_MAX_CHANGE_DENOMINATOR);
+
+    if parent.gas_used > gas_target {
+        let gas_used_delta = U256::from(parent.gas_used - gas_target);
+        let base_fee_delta = std::cmp::max(base_fee * gas_used_delta / denominator, U256::from(1));
+        base_fee + base_fee_delta
+    } else {
+        let gas_used_delta = U256::from(gas_target - parent.gas_used);
+        let base_fee_delta = base_fee * gas_used_delta / denominator;
+        base_fee.saturating_sub(base_fee_delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_base_fee_per_gas;
+    use crate::helios::types::ExecutionPayloadHeader;
+    use alloy_primitives::U256;
+
+    fn header(gas_used: u64, gas_limit: u64, base_fee: u64) -> ExecutionPayloadHeader {
+        ExecutionPayloadHeader { gas_used, gas_limit, base_fee_per_gas: U256::from(base_fee), ..Default::default() }
+    }
+
+    #[test]
+    fn test_next_base_fee() {
+        assert_eq!(next_base_fee_per_gas(&header(15_000_000, 30_000_000, 1_000_000_000)), U256::from(1_000_000_000));
+        assert_eq!(next_base_fee_per_gas(&header(30_000_000, 30_000_000, 1_000_000_000)), U256::from(1_125_000_000));
+        assert_eq!(next_base_fee_per_gas(&header(0, 30_000_000, 1_000_000_000)), U256::from(875_000_000));
+        assert_eq!(next_base_fee_per_gas(&header(15_000_001, 30_000_000, 1)), U256::from(2));
+    }
+}
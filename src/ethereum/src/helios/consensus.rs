@@ -7,8 +7,8 @@ use tree_hash::TreeHash;
 
 use crate::helios::errors::ConsensusError;
 use crate::helios::proof::{
-    is_current_committee_proof_valid, is_execution_payload_proof_valid, is_finality_proof_valid,
-    is_next_committee_proof_valid,
+    is_ancestry_proof_valid, is_current_committee_proof_valid, is_execution_payload_proof_valid,
+    is_finality_proof_valid, is_next_committee_proof_valid, AncestryProof,
 };
 use crate::helios::spec::ConsensusSpec;
 use crate::helios::types::bls::{PublicKey, Signature};
@@ -80,6 +80,23 @@ pub fn verify_optimistic_update<S: ConsensusSpec>(
     verify_generic_update::<S>(&update, expected_current_slot, store, genesis_root, forks)
 }
 
+/// Verifies that the proven header is an ancestor of `anchor`, which has to be finalized.
+pub fn verify_ancestry<S: ConsensusSpec>(
+    proof: &AncestryProof,
+    anchor: &BeaconBlockHeader,
+    forks: &Forks,
+) -> Result<()> {
+    if !is_valid_header::<S>(&proof.header, forks) {
+        return Err(ConsensusError::InvalidExecutionPayloadProof.into());
+    }
+
+    if !is_ancestry_proof_valid::<S>(&proof.header.beacon, anchor, &proof.branch, forks) {
+        return Err(ConsensusError::InvalidAncestryProof.into());
+    }
+
+    Ok(())
+}
+
 pub fn apply_bootstrap<S: ConsensusSpec>(store: &mut LightClientStore<S>, bootstrap: &Bootstrap<S>) {
     *store = LightClientStore {
         finalized_header: bootstrap.header().clone(),
@@ -306,7 +323,9 @@ pub fn verify_generic_update<S: ConsensusSpec>(
 
     let pks = get_participating_keys(sync_committee, &update.sync_aggregate.sync_committee_bits)?;
 
-    let fork_version = calculate_fork_version::<S>(forks, update.signature_slot.saturating_sub(1));
+    let fork_slot = update.signature_slot.saturating_sub(1);
+    let fork_version = calculate_fork_version::<S>(forks, fork_slot)
+        .ok_or(ConsensusError::UnknownFork(fork_slot / S::slots_per_epoch()))?;
     let fork_data_root = compute_fork_data_root(fork_version, genesis_root);
     let is_valid_sig = verify_sync_committee_signature(
         pks.as_slice(),
@@ -336,11 +355,11 @@ pub fn force_update<S: ConsensusSpec>(store: &mut LightClientStore<S>, current_s
     }
 }
 
-pub fn expected_current_slot(now_ns: u64, genesis_time: u64) -> u64 {
+pub fn expected_current_slot<S: ConsensusSpec>(now_ns: u64, genesis_time: u64) -> u64 {
     let now = now_ns / 1_000_000_000;
     let since_genesis = now - genesis_time;
 
-    since_genesis / 12
+    since_genesis / S::seconds_per_slot()
 }
 
 pub fn calc_sync_period<S: ConsensusSpec>(slot: u64) -> u64 {
@@ -443,3 +462,90 @@ fn is_valid_header<S: ConsensusSpec>(header: &LightClientHeader, _: &Forks) -> b
 
     proof_valid
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helios::spec::{MainnetConsensusSpec, MinimalConsensusSpec};
+    use crate::helios::types::SyncCommittee;
+    use ic_bls12_381::{
+        hash_to_curve::{ExpandMsgXmd, HashToCurve},
+        G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+    };
+    use sha2::{Digest, Sha256};
+    use ssz::Decode;
+    use ssz_types::FixedVector;
+
+    type S = MinimalConsensusSpec;
+
+    const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+    /// Header with a valid execution branch of zero hashes.
+    fn header(slot: u64) -> LightClientHeader {
+        let mut header = LightClientHeader::default();
+        header.execution.block_number = slot;
+        header.execution_branch = FixedVector::from(vec![B256::ZERO; 4]);
+
+        let mut root = header.execution.tree_hash_root();
+        for (i, node) in header.execution_branch.iter().enumerate() {
+            let mut hasher = Sha256::new();
+            if (9 >> i) & 1 == 1 {
+                hasher.update(node);
+                hasher.update(root);
+            } else {
+                hasher.update(root);
+                hasher.update(node);
+            }
+            root = B256::from_slice(&hasher.finalize());
+        }
+
+        header.beacon.slot = slot;
+        header.beacon.body_root = root;
+        header
+    }
+
+    #[test]
+    fn test_minimal_spec_update() {
+        let forks: Forks =
+            serde_json::from_str(r#"[{ "name": "deneb", "epoch": 0, "fork_version": "0x04000000" }]"#).unwrap();
+        let genesis_root = B256::repeat_byte(1);
+        let genesis_time = 1_000;
+        // 40 slots of 6 seconds, only 20 of the 12 seconds of mainnet
+        let now_ns = (genesis_time + 40 * 6) * 1_000_000_000;
+        assert_eq!(expected_current_slot::<S>(now_ns, genesis_time), 40);
+        assert_eq!(expected_current_slot::<MainnetConsensusSpec>(now_ns, genesis_time), 20);
+
+        let secret_keys: Vec<Scalar> = (1..=S::sync_committee_size()).map(Scalar::from).collect();
+        let pubkeys = secret_keys
+            .iter()
+            .map(|key| {
+                let point = G1Affine::from(G1Projective::generator() * key);
+                PublicKey::from_ssz_bytes(&point.to_compressed()).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let store = LightClientStore::<S> {
+            finalized_header: header(8),
+            current_sync_committee: SyncCommittee { pubkeys: FixedVector::from(pubkeys), ..Default::default() },
+            ..Default::default()
+        };
+
+        let mut update = GenericUpdate::<S> { attested_header: header(29), signature_slot: 30, ..Default::default() };
+        for i in 0..S::sync_committee_size() as usize {
+            update.sync_aggregate.sync_committee_bits.set(i, true).unwrap();
+        }
+
+        let fork_version = calculate_fork_version::<S>(&forks, 29).unwrap();
+        let fork_data_root = compute_fork_data_root(fork_version, genesis_root);
+        let signing_root = compute_committee_sign_root(update.attested_header.beacon.tree_hash_root(), fork_data_root);
+        let message = <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(signing_root, DST);
+        let signature = G2Affine::from(message * secret_keys.iter().sum::<Scalar>());
+        update.sync_aggregate.sync_committee_signature = Signature::from_ssz_bytes(&signature.to_compressed()).unwrap();
+
+        let current_slot = expected_current_slot::<S>(now_ns, genesis_time);
+        verify_generic_update(&update, current_slot, &store, genesis_root, &forks).unwrap();
+
+        let mainnet_slot = expected_current_slot::<MainnetConsensusSpec>(now_ns, genesis_time);
+        let err = verify_generic_update(&update, mainnet_slot, &store, genesis_root, &forks).unwrap_err();
+        assert!(matches!(err.downcast_ref::<ConsensusError>(), Some(ConsensusError::InvalidTimestamp)));
+    }
+}
@@ -19,6 +19,8 @@ pub enum ConsensusError {
     InvalidCurrentSyncCommitteeProof,
     #[error("invalid execution payload proof")]
     InvalidExecutionPayloadProof,
+    #[error("invalid ancestry proof")]
+    InvalidAncestryProof,
     #[error("invalid sync committee signature")]
     InvalidSignature,
     #[error("invalid header hash found: {0}, expected: {1}")]
@@ -29,4 +31,6 @@ pub enum ConsensusError {
     CheckpointTooOld,
     #[error("consensus rpc is for the incorrect network")]
     IncorrectRpcNetwork,
+    #[error("no fork scheduled at epoch: {0}")]
+    UnknownFork(u64),
 }
@@ -1,12 +1,52 @@
 use alloy_primitives::B256;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use ssz_derive::{Decode, Encode};
 use tree_hash::TreeHash;
 
 use crate::helios::{
     spec::ConsensusSpec,
-    types::{BeaconBlockHeader, ExecutionPayloadHeader, Forks, SyncCommittee},
+    types::{
+        gindex_to_index_and_depth, BeaconBlockHeader, ExecutionPayloadHeader, Forks, HistoricalSummary,
+        LightClientHeader, SyncCommittee,
+    },
 };
 
+/// Depth of the `historical_summaries` list including the mixed in length.
+const HISTORICAL_SUMMARIES_DEPTH: usize = 25;
+
+/// Branch of a block root within the `block_roots` of the anchor state, for blocks of the last
+/// `SLOTS_PER_HISTORICAL_ROOT` slots before it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Encode, Decode, PartialEq)]
+pub struct BlockRootsProof {
+    pub branch: Vec<B256>,
+}
+
+/// Branch of a block root within the block summary root of its period, and of that period's
+/// summary within the `historical_summaries` of the anchor state.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Encode, Decode, PartialEq)]
+pub struct HistoricalSummaryProof {
+    pub block_root_branch: Vec<B256>,
+    pub summary: HistoricalSummary,
+    pub summary_branch: Vec<B256>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Encode, Decode, PartialEq)]
+#[ssz(enum_behaviour = "union")]
+pub enum AncestryBranch {
+    BlockRoots(BlockRootsProof),
+    HistoricalSummary(HistoricalSummaryProof),
+}
+
+/// Proof that `header` is an ancestor of the finalized header at `anchor_slot`, through the state
+/// root of the anchor.
+#[derive(Serialize, Deserialize, Debug, Clone, Encode, Decode, PartialEq)]
+pub struct AncestryProof {
+    pub header: LightClientHeader,
+    pub anchor_slot: u64,
+    pub branch: AncestryBranch,
+}
+
 pub fn is_finality_proof_valid(
     attested_header: &BeaconBlockHeader,
     finality_header: &BeaconBlockHeader,
@@ -14,7 +54,8 @@ pub fn is_finality_proof_valid(
     current_epoch: u64,
     forks: &Forks,
 ) -> bool {
-    let (index, depth) = if current_epoch >= forks.electra.epoch { (41, 7) } else { (41, 6) };
+    let Some(fork) = forks.at_epoch(current_epoch) else { return false };
+    let (index, depth) = gindex_to_index_and_depth(fork.light_client_gindices().finalized_root);
 
     is_proof_valid(attested_header.state_root, finality_header, finality_branch, depth, index)
 }
@@ -26,7 +67,8 @@ pub fn is_next_committee_proof_valid<S: ConsensusSpec>(
     current_epoch: u64,
     forks: &Forks,
 ) -> bool {
-    let (index, depth) = if current_epoch >= forks.electra.epoch { (23, 6) } else { (23, 5) };
+    let Some(fork) = forks.at_epoch(current_epoch) else { return false };
+    let (index, depth) = gindex_to_index_and_depth(fork.light_client_gindices().next_sync_committee);
 
     is_proof_valid(attested_header.state_root, next_committee, next_committee_branch, depth, index)
 }
@@ -38,7 +80,8 @@ pub fn is_current_committee_proof_valid<S: ConsensusSpec>(
     current_epoch: u64,
     forks: &Forks,
 ) -> bool {
-    let (index, depth) = if current_epoch >= forks.electra.epoch { (22, 6) } else { (22, 5) };
+    let Some(fork) = forks.at_epoch(current_epoch) else { return false };
+    let (index, depth) = gindex_to_index_and_depth(fork.light_client_gindices().current_sync_committee);
 
     is_proof_valid(attested_header.state_root, current_committee, current_committee_branch, depth, index)
 }
@@ -51,6 +94,90 @@ pub fn is_execution_payload_proof_valid(
     is_proof_valid(attested_header.body_root, execution, execution_branch, 4, 9)
 }
 
+pub fn is_ancestry_proof_valid<S: ConsensusSpec>(
+    header: &BeaconBlockHeader,
+    anchor: &BeaconBlockHeader,
+    branch: &AncestryBranch,
+    forks: &Forks,
+) -> bool {
+    if header.slot >= anchor.slot {
+        return false;
+    }
+
+    let Some(fork) = forks.at_epoch(anchor.slot / S::slots_per_epoch()) else { return false };
+    let gindices = fork.name.ancestry_gindices();
+    let slots_per_historical_root = S::slots_per_historical_root();
+    let roots_depth = slots_per_historical_root.ilog2() as usize;
+    let roots_index = (header.slot % slots_per_historical_root) as usize;
+
+    match branch {
+        AncestryBranch::BlockRoots(proof) => {
+            if anchor.slot - header.slot > slots_per_historical_root {
+                return false;
+            }
+
+            let (index, depth) = gindex_to_index_and_depth(gindices.block_roots);
+            is_proof_valid(
+                anchor.state_root,
+                header,
+                &proof.branch,
+                depth + roots_depth,
+                (index << roots_depth) | roots_index,
+            )
+        }
+        AncestryBranch::HistoricalSummary(proof) => {
+            let Some(summary_index) = historical_summary_index::<S>(header.slot, forks) else { return false };
+            let (index, depth) = gindex_to_index_and_depth(gindices.historical_summaries);
+
+            is_proof_valid(proof.summary.block_summary_root, header, &proof.block_root_branch, roots_depth, roots_index)
+                && is_proof_valid(
+                    anchor.state_root,
+                    &proof.summary,
+                    &proof.summary_branch,
+                    depth + HISTORICAL_SUMMARIES_DEPTH,
+                    (index << HISTORICAL_SUMMARIES_DEPTH) | summary_index,
+                )
+        }
+    }
+}
+
+/// Index of the `historical_summaries` entry of the period of `slot`. Summaries are appended at
+/// the end of every period from the one Capella activated in on.
+pub fn historical_summary_index<S: ConsensusSpec>(slot: u64, forks: &Forks) -> Option<usize> {
+    let slots_per_historical_root = S::slots_per_historical_root();
+    let first_period = forks.capella_epoch()? * S::slots_per_epoch() / slots_per_historical_root;
+    let period = slot / slots_per_historical_root;
+
+    period.checked_sub(first_period).map(|index| index as usize)
+}
+
+/// Branch of the leaf at `index` of a tree of `depth`, whose leaves past `leaves` are zero.
+pub fn merkle_branch(leaves: &[B256], depth: usize, index: usize) -> Vec<B256> {
+    let mut branch = Vec::with_capacity(depth);
+    let mut layer = leaves.to_vec();
+    let mut zero = B256::ZERO;
+    let mut index = index;
+
+    for _ in 0..depth {
+        branch.push(layer.get(index ^ 1).copied().unwrap_or(zero));
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair.get(1).copied().unwrap_or(zero)))
+            .collect();
+        zero = hash_pair(zero, zero);
+        index /= 2;
+    }
+
+    branch
+}
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
 fn is_proof_valid<T: TreeHash>(root: B256, leaf_object: &T, branch: &[B256], depth: usize, index: usize) -> bool {
     if branch.len() != depth {
         return false;
@@ -8,6 +8,15 @@ pub trait ConsensusSpec: 'static + Default + Sync + Send + Clone + Debug + Parti
     type SlotsPerEpoch: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
     type EpochsPerSyncCommitteePeriod: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
     type SyncCommitteeSize: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type SlotsPerHistoricalRoot: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type EpochsPerHistoricalVector: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type EpochsPerSlashingsVector: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type SlotsPerEth1VotingPeriod: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type PendingPartialWithdrawalsLimit: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type PendingConsolidationsLimit: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+    type ProposerLookaheadSize: Unsigned + Default + Debug + Sync + Send + Clone + PartialEq;
+
+    fn seconds_per_slot() -> u64;
 
     fn slots_per_epoch() -> u64 {
         Self::SlotsPerEpoch::to_u64()
@@ -24,6 +33,10 @@ pub trait ConsensusSpec: 'static + Default + Sync + Send + Clone + Debug + Parti
     fn sync_committee_size() -> u64 {
         Self::SyncCommitteeSize::to_u64()
     }
+
+    fn slots_per_historical_root() -> u64 {
+        Self::SlotsPerHistoricalRoot::to_u64()
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
@@ -34,6 +47,17 @@ impl ConsensusSpec for MainnetConsensusSpec {
     type SlotsPerEpoch = typenum::U32;
     type EpochsPerSyncCommitteePeriod = typenum::U256;
     type SyncCommitteeSize = typenum::U512;
+    type SlotsPerHistoricalRoot = typenum::U8192;
+    type EpochsPerHistoricalVector = typenum::U65536;
+    type EpochsPerSlashingsVector = typenum::U8192;
+    type SlotsPerEth1VotingPeriod = typenum::U2048;
+    type PendingPartialWithdrawalsLimit = typenum::U134217728;
+    type PendingConsolidationsLimit = typenum::U262144;
+    type ProposerLookaheadSize = typenum::U64;
+
+    fn seconds_per_slot() -> u64 {
+        12
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
@@ -44,4 +68,15 @@ impl ConsensusSpec for MinimalConsensusSpec {
     type SlotsPerEpoch = typenum::U8;
     type EpochsPerSyncCommitteePeriod = typenum::U8;
     type SyncCommitteeSize = typenum::U32;
+    type SlotsPerHistoricalRoot = typenum::U64;
+    type EpochsPerHistoricalVector = typenum::U64;
+    type EpochsPerSlashingsVector = typenum::U64;
+    type SlotsPerEth1VotingPeriod = typenum::U32;
+    type PendingPartialWithdrawalsLimit = typenum::U64;
+    type PendingConsolidationsLimit = typenum::U64;
+    type ProposerLookaheadSize = typenum::U16;
+
+    fn seconds_per_slot() -> u64 {
+        6
+    }
 }
@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::Error, Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
 use ssz_types::{
     serde_utils::{hex_fixed_vec, hex_var_list},
@@ -18,11 +18,18 @@ pub struct ByteList<N: typenum::Unsigned> {
     pub inner: VariableList<u8, N>,
 }
 
+// Binary formats carry the raw bytes instead of hex strings.
 impl<'de, N: typenum::Unsigned> serde::Deserialize<'de> for ByteVector<N> {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            let inner = FixedVector::new(bytes).map_err(|e| D::Error::custom(format!("{:?}", e)))?;
+            return Ok(Self { inner });
+        }
+
         let inner = hex_fixed_vec::deserialize(deserializer)?;
         Ok(Self { inner })
     }
@@ -33,6 +40,10 @@ impl<N: typenum::Unsigned> Serialize for ByteVector<N> {
     where
         S: serde::Serializer,
     {
+        if !serializer.is_human_readable() {
+            return self.inner[..].serialize(serializer);
+        }
+
         hex_fixed_vec::serialize(&self.inner, serializer)
     }
 }
@@ -42,6 +53,12 @@ impl<'de, N: typenum::Unsigned> Deserialize<'de> for ByteList<N> {
     where
         D: serde::Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            let inner = VariableList::new(bytes).map_err(|e| D::Error::custom(format!("{:?}", e)))?;
+            return Ok(Self { inner });
+        }
+
         let inner = hex_var_list::deserialize(deserializer)?;
         Ok(Self { inner })
     }
@@ -52,6 +69,10 @@ impl<N: typenum::Unsigned> serde::Serialize for ByteList<N> {
     where
         S: serde::Serializer,
     {
+        if !serializer.is_human_readable() {
+            return self.inner[..].serialize(serializer);
+        }
+
         hex_var_list::serialize(&self.inner, serializer)
     }
 }
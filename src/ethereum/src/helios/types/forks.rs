@@ -0,0 +1,251 @@
+use alloy_primitives::FixedBytes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ForkName {
+    #[default]
+    #[serde(alias = "phase0")]
+    Genesis,
+    Altair,
+    Bellatrix,
+    Capella,
+    Deneb,
+    Electra,
+    Fulu,
+}
+
+impl ForkName {
+    /// Electra grew the beacon state, which deepened the light client branches. Forks before it
+    /// share the Deneb containers and forks after it the Electra ones.
+    pub fn has_electra_containers(self) -> bool {
+        self >= ForkName::Electra
+    }
+
+    pub fn default_light_client_gindices(self) -> LightClientGindices {
+        if self.has_electra_containers() {
+            LightClientGindices { finalized_root: 169, current_sync_committee: 86, next_sync_committee: 87 }
+        } else {
+            LightClientGindices { finalized_root: 105, current_sync_committee: 54, next_sync_committee: 55 }
+        }
+    }
+
+    /// Gindices of the beacon state fields ancestry proofs go through. Capella added the last
+    /// field of the Deneb state, so earlier forks share its layout.
+    pub fn ancestry_gindices(self) -> AncestryGindices {
+        if self.has_electra_containers() {
+            AncestryGindices { block_roots: 69, historical_summaries: 91 }
+        } else {
+            AncestryGindices { block_roots: 37, historical_summaries: 59 }
+        }
+    }
+}
+
+/// Generalized indices of the light client proofs into the beacon state.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightClientGindices {
+    pub finalized_root: u64,
+    pub current_sync_committee: u64,
+    pub next_sync_committee: u64,
+}
+
+/// Generalized indices of the `block_roots` and `historical_summaries` fields of the beacon state.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AncestryGindices {
+    pub block_roots: u64,
+    pub historical_summaries: u64,
+}
+
+/// Splits a generalized index into the index of the leaf within its depth and the depth, which is
+/// also the length of its merkle branch.
+pub fn gindex_to_index_and_depth(gindex: u64) -> (usize, usize) {
+    let depth = gindex.max(1).ilog2();
+    let index = gindex - (1 << depth);
+
+    (index as usize, depth as usize)
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Fork {
+    pub name: ForkName,
+    pub epoch: u64,
+    pub fork_version: FixedBytes<4>,
+    /// Falls back to the gindices of `name` when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light_client_gindices: Option<LightClientGindices>,
+}
+
+impl Fork {
+    pub fn light_client_gindices(&self) -> LightClientGindices {
+        self.light_client_gindices
+            .unwrap_or_else(|| self.name.default_light_client_gindices())
+    }
+}
+
+/// Fork schedule ordered by activation epoch. Several forks may activate at the same epoch, as on
+/// Holesky, Sepolia and devnets, in which case the latest of them is active from it on.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(try_from = "ForksRepr", into = "Vec<Fork>")]
+pub struct Forks(Vec<Fork>);
+
+impl Forks {
+    pub fn new(forks: Vec<Fork>) -> Result<Self, String> {
+        if forks.is_empty() {
+            return Err("Fork schedule is empty".into());
+        }
+
+        let is_ordered = forks
+            .windows(2)
+            .all(|pair| pair[0].epoch <= pair[1].epoch && pair[0].name < pair[1].name);
+        if !is_ordered {
+            return Err("Forks must be ordered by epoch and name".into());
+        }
+
+        Ok(Self(forks))
+    }
+
+    /// Returns the fork active at `epoch`, the latest one when several activate at the same epoch.
+    pub fn at_epoch(&self, epoch: u64) -> Option<&Fork> {
+        self.0.iter().rev().find(|fork| fork.epoch <= epoch)
+    }
+
+    /// Epoch from which on the beacon state records `historical_summaries`.
+    pub fn capella_epoch(&self) -> Option<u64> {
+        self.0.iter().find(|fork| fork.name >= ForkName::Capella).map(|fork| fork.epoch)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Fork> {
+        self.0.iter()
+    }
+}
+
+impl From<Forks> for Vec<Fork> {
+    fn from(forks: Forks) -> Self {
+        forks.0
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyFork {
+    epoch: u64,
+    fork_version: FixedBytes<4>,
+}
+
+/// Fixed set of forks of earlier configs.
+#[derive(Deserialize)]
+struct LegacyForks {
+    genesis: LegacyFork,
+    altair: LegacyFork,
+    bellatrix: LegacyFork,
+    capella: LegacyFork,
+    deneb: LegacyFork,
+    electra: LegacyFork,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ForksRepr {
+    List(Vec<Fork>),
+    Legacy(LegacyForks),
+}
+
+impl TryFrom<ForksRepr> for Forks {
+    type Error = String;
+
+    fn try_from(repr: ForksRepr) -> Result<Self, Self::Error> {
+        let forks = match repr {
+            ForksRepr::List(forks) => forks,
+            ForksRepr::Legacy(legacy) => [
+                (ForkName::Genesis, legacy.genesis),
+                (ForkName::Altair, legacy.altair),
+                (ForkName::Bellatrix, legacy.bellatrix),
+                (ForkName::Capella, legacy.capella),
+                (ForkName::Deneb, legacy.deneb),
+                (ForkName::Electra, legacy.electra),
+            ]
+            .into_iter()
+            .map(|(name, fork)| Fork {
+                name,
+                epoch: fork.epoch,
+                fork_version: fork.fork_version,
+                light_client_gindices: None,
+            })
+            .collect(),
+        };
+
+        Forks::new(forks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gindex_to_index_and_depth, ForkName, Forks};
+
+    #[test]
+    fn test_fork_schedule() {
+        let legacy = r#"{
+            "genesis": { "epoch": 0, "fork_version": "0x00000000" },
+            "altair": { "epoch": 74240, "fork_version": "0x01000000" },
+            "bellatrix": { "epoch": 144896, "fork_version": "0x02000000" },
+            "capella": { "epoch": 194048, "fork_version": "0x03000000" },
+            "deneb": { "epoch": 269568, "fork_version": "0x04000000" },
+            "electra": { "epoch": 364032, "fork_version": "0x05000000" }
+        }"#;
+        let forks: Forks = serde_json::from_str(legacy).unwrap();
+
+        assert_eq!(forks.at_epoch(0).unwrap().name, ForkName::Genesis);
+        assert_eq!(forks.at_epoch(364031).unwrap().name, ForkName::Deneb);
+        assert_eq!(forks.at_epoch(u64::MAX).unwrap().name, ForkName::Electra);
+
+        let serialized = serde_json::to_string(&forks).unwrap();
+        let forks: Forks = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(forks.iter().count(), 6);
+
+        let unordered = r#"[
+            { "name": "deneb", "epoch": 10, "fork_version": "0x04000000" },
+            { "name": "capella", "epoch": 20, "fork_version": "0x03000000" }
+        ]"#;
+        assert!(serde_json::from_str::<Forks>(unordered).is_err());
+    }
+
+    #[test]
+    fn test_forks_at_same_epoch() {
+        let holesky = r#"{
+            "genesis": { "epoch": 0, "fork_version": "0x01017000" },
+            "altair": { "epoch": 0, "fork_version": "0x02017000" },
+            "bellatrix": { "epoch": 0, "fork_version": "0x03017000" },
+            "capella": { "epoch": 256, "fork_version": "0x04017000" },
+            "deneb": { "epoch": 29696, "fork_version": "0x05017000" },
+            "electra": { "epoch": 115968, "fork_version": "0x06017000" }
+        }"#;
+        let forks: Forks = serde_json::from_str(holesky).unwrap();
+        assert_eq!(forks.at_epoch(0).unwrap().name, ForkName::Bellatrix);
+        assert_eq!(forks.at_epoch(256).unwrap().name, ForkName::Capella);
+
+        let devnet = r#"[
+            { "name": "genesis", "epoch": 0, "fork_version": "0x10000038" },
+            { "name": "altair", "epoch": 0, "fork_version": "0x20000038" },
+            { "name": "bellatrix", "epoch": 0, "fork_version": "0x30000038" },
+            { "name": "capella", "epoch": 0, "fork_version": "0x40000038" },
+            { "name": "deneb", "epoch": 0, "fork_version": "0x50000038" },
+            { "name": "electra", "epoch": 0, "fork_version": "0x60000038" }
+        ]"#;
+        let forks: Forks = serde_json::from_str(devnet).unwrap();
+        assert_eq!(forks.at_epoch(0).unwrap().name, ForkName::Electra);
+        assert_eq!(forks.capella_epoch(), Some(0));
+
+        let duplicate = r#"[
+            { "name": "deneb", "epoch": 0, "fork_version": "0x50000038" },
+            { "name": "deneb", "epoch": 0, "fork_version": "0x50000038" }
+        ]"#;
+        assert!(serde_json::from_str::<Forks>(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_gindex_to_index_and_depth() {
+        assert_eq!(gindex_to_index_and_depth(105), (41, 6));
+        assert_eq!(gindex_to_index_and_depth(169), (41, 7));
+        assert_eq!(gindex_to_index_and_depth(55), (23, 5));
+        assert_eq!(gindex_to_index_and_depth(86), (22, 6));
+    }
+}
@@ -1,9 +1,11 @@
 use crate::helios::spec::ConsensusSpec;
-use alloy_primitives::{Address, FixedBytes, B256, U256};
-use serde::{Deserialize, Serialize};
+use alloy_primitives::{Address, B256, U256};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use ssz::{Decode as _, Encode as _};
 use ssz_derive::{Decode, Encode};
 use ssz_types::{BitVector, FixedVector};
 use superstruct::superstruct;
+use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
 use self::{
@@ -13,7 +15,14 @@ use self::{
 
 pub mod bls;
 mod bytes;
+mod forks;
 mod serde_utils;
+mod state;
+
+pub use forks::{gindex_to_index_and_depth, AncestryGindices, Fork, ForkName, Forks, LightClientGindices};
+pub use state::{
+    read_block_roots, BeaconState, BeaconStateDeneb, BeaconStateElectra, BeaconStateFulu, HistoricalSummary,
+};
 
 pub type LogsBloom = ByteVector<typenum::U256>;
 
@@ -70,14 +79,12 @@ pub struct ExecutionPayloadHeader {
 #[superstruct(
     variants(Deneb, Electra),
     variant_attributes(
-        derive(Serialize, Deserialize, Debug, Decode, Clone),
+        derive(Serialize, Deserialize, Debug, Encode, Decode, Clone),
         serde(deny_unknown_fields),
         serde(bound = "S: ConsensusSpec"),
     )
 )]
-#[derive(Serialize, Deserialize, Debug, Decode, Clone)]
-#[serde(untagged)]
-#[serde(bound = "S: ConsensusSpec")]
+#[derive(Debug, Encode, Decode, Clone)]
 #[ssz(enum_behaviour = "transparent")]
 pub struct Bootstrap<S: ConsensusSpec> {
     pub header: LightClientHeader,
@@ -88,7 +95,58 @@ pub struct Bootstrap<S: ConsensusSpec> {
     pub current_sync_committee_branch: FixedVector<B256, typenum::U6>,
 }
 
+/// Human readable formats keep the untagged JSON of the beacon API. Binary formats carry the SSZ
+/// encoding instead, which also tells the fork variants apart.
+macro_rules! impl_fork_serde {
+    ($name:ident, $($variant:ident($inner:ident)),+) => {
+        impl<S: ConsensusSpec> Serialize for $name<S> {
+            fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+                if !serializer.is_human_readable() {
+                    return self.as_ssz_bytes().serialize(serializer);
+                }
+
+                match self {
+                    $($name::$variant(inner) => inner.serialize(serializer),)+
+                }
+            }
+        }
+
+        impl<'de, S: ConsensusSpec> Deserialize<'de> for $name<S> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if !deserializer.is_human_readable() {
+                    let bytes = Vec::<u8>::deserialize(deserializer)?;
+                    return Self::from_ssz_bytes(&bytes).map_err(|e| D::Error::custom(format!("{:?}", e)));
+                }
+
+                #[derive(Deserialize)]
+                #[serde(untagged)]
+                #[serde(bound = "S: ConsensusSpec")]
+                enum Repr<S: ConsensusSpec> {
+                    $($variant($inner<S>),)+
+                }
+
+                Ok(match Repr::<S>::deserialize(deserializer)? {
+                    $(Repr::$variant(inner) => $name::$variant(inner),)+
+                })
+            }
+        }
+    };
+}
+
+impl_fork_serde!(Bootstrap, Deneb(BootstrapDeneb), Electra(BootstrapElectra));
+impl_fork_serde!(Update, Deneb(UpdateDeneb), Electra(UpdateElectra));
+impl_fork_serde!(FinalityUpdate, Deneb(FinalityUpdateDeneb), Electra(FinalityUpdateElectra));
+
 impl<S: ConsensusSpec> Bootstrap<S> {
+    /// Decodes the container used at `fork` instead of matching on branch lengths.
+    pub fn deserialize_for_fork<'de, D: Deserializer<'de>>(fork: ForkName, deserializer: D) -> Result<Self, D::Error> {
+        if fork.has_electra_containers() {
+            BootstrapElectra::deserialize(deserializer).map(Bootstrap::Electra)
+        } else {
+            BootstrapDeneb::deserialize(deserializer).map(Bootstrap::Deneb)
+        }
+    }
+
     pub fn current_sync_committee_branch(&self) -> &[B256] {
         match self {
             Bootstrap::Deneb(inner) => &inner.current_sync_committee_branch,
@@ -100,14 +158,12 @@ impl<S: ConsensusSpec> Bootstrap<S> {
 #[superstruct(
     variants(Deneb, Electra),
     variant_attributes(
-        derive(Serialize, Deserialize, Debug, Clone, Decode,),
+        derive(Serialize, Deserialize, Debug, Clone, Encode, Decode,),
         serde(deny_unknown_fields),
         serde(bound = "S: ConsensusSpec"),
     )
 )]
-#[derive(Serialize, Deserialize, Debug, Clone, Decode)]
-#[serde(untagged)]
-#[serde(bound = "S: ConsensusSpec")]
+#[derive(Debug, Clone, Encode, Decode)]
 #[ssz(enum_behaviour = "transparent")]
 pub struct Update<S: ConsensusSpec> {
     pub attested_header: LightClientHeader,
@@ -127,6 +183,15 @@ pub struct Update<S: ConsensusSpec> {
 }
 
 impl<S: ConsensusSpec> Update<S> {
+    /// Decodes the container used at `fork` instead of matching on branch lengths.
+    pub fn deserialize_for_fork<'de, D: Deserializer<'de>>(fork: ForkName, deserializer: D) -> Result<Self, D::Error> {
+        if fork.has_electra_containers() {
+            UpdateElectra::deserialize(deserializer).map(Update::Electra)
+        } else {
+            UpdateDeneb::deserialize(deserializer).map(Update::Deneb)
+        }
+    }
+
     pub fn next_sync_committee_branch(&self) -> &[B256] {
         match self {
             Update::Deneb(inner) => &inner.next_sync_committee_branch,
@@ -145,14 +210,12 @@ impl<S: ConsensusSpec> Update<S> {
 #[superstruct(
     variants(Deneb, Electra),
     variant_attributes(
-        derive(Serialize, Deserialize, Debug, Clone, Decode,),
+        derive(Serialize, Deserialize, Debug, Clone, Encode, Decode,),
         serde(deny_unknown_fields),
         serde(bound = "S: ConsensusSpec"),
     )
 )]
-#[derive(Serialize, Deserialize, Debug, Clone, Decode)]
-#[serde(untagged)]
-#[serde(bound = "S: ConsensusSpec")]
+#[derive(Debug, Clone, Encode, Decode)]
 #[ssz(enum_behaviour = "transparent")]
 pub struct FinalityUpdate<S: ConsensusSpec> {
     pub attested_header: LightClientHeader,
@@ -167,6 +230,15 @@ pub struct FinalityUpdate<S: ConsensusSpec> {
 }
 
 impl<S: ConsensusSpec> FinalityUpdate<S> {
+    /// Decodes the container used at `fork` instead of matching on branch lengths.
+    pub fn deserialize_for_fork<'de, D: Deserializer<'de>>(fork: ForkName, deserializer: D) -> Result<Self, D::Error> {
+        if fork.has_electra_containers() {
+            FinalityUpdateElectra::deserialize(deserializer).map(FinalityUpdate::Electra)
+        } else {
+            FinalityUpdateDeneb::deserialize(deserializer).map(FinalityUpdate::Deneb)
+        }
+    }
+
     pub fn finality_branch(&self) -> &[B256] {
         match self {
             FinalityUpdate::Deneb(inner) => &inner.finality_branch,
@@ -183,13 +255,19 @@ pub struct OptimisticUpdate<S: ConsensusSpec> {
     pub signature_slot: u64,
 }
 
-#[derive(Default, Debug, Clone, Serialize, Deserialize, Decode, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Encode, Decode, PartialEq)]
 pub struct LightClientHeader {
     pub beacon: BeaconBlockHeader,
     pub execution: ExecutionPayloadHeader,
     pub execution_branch: FixedVector<B256, typenum::U4>,
 }
 
+impl LightClientHeader {
+    pub fn beacon_block_root(&self) -> B256 {
+        self.beacon.tree_hash_root()
+    }
+}
+
 #[derive(Debug, Clone, Default, Encode, TreeHash, Serialize, Deserialize, Decode, PartialEq)]
 pub struct SyncCommittee<S: ConsensusSpec> {
     pub pubkeys: FixedVector<PublicKey, S::SyncCommitteeSize>,
@@ -202,22 +280,6 @@ pub struct SyncAggregate<S: ConsensusSpec> {
     pub sync_committee_signature: Signature,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-pub struct Forks {
-    pub genesis: Fork,
-    pub altair: Fork,
-    pub bellatrix: Fork,
-    pub capella: Fork,
-    pub deneb: Fork,
-    pub electra: Fork,
-}
-
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
-pub struct Fork {
-    pub epoch: u64,
-    pub fork_version: FixedBytes<4>,
-}
-
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct GenericUpdate<S: ConsensusSpec> {
     pub attested_header: LightClientHeader,
@@ -1,3 +1,5 @@
+//! The beacon API quotes integers, binary formats carry them as they are.
+
 pub mod u64 {
     use serde::{de::Error, Deserializer, Serializer};
 
@@ -5,6 +7,10 @@ pub mod u64 {
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            return serializer.serialize_u64(*value);
+        }
+
         serializer.serialize_str(&value.to_string())
     }
 
@@ -12,6 +18,10 @@ pub mod u64 {
     where
         D: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return serde::Deserialize::deserialize(deserializer);
+        }
+
         let val: String = serde::Deserialize::deserialize(deserializer)?;
         val.parse().map_err(D::Error::custom)
     }
@@ -25,6 +35,10 @@ pub mod u256 {
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            return serde::Serialize::serialize(value, serializer);
+        }
+
         serializer.serialize_str(&value.to_string())
     }
 
@@ -32,6 +46,10 @@ pub mod u256 {
     where
         D: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return serde::Deserialize::deserialize(deserializer);
+        }
+
         let val: String = serde::Deserialize::deserialize(deserializer)?;
         val.parse().map_err(D::Error::custom)
     }
@@ -0,0 +1,349 @@
+use alloy_primitives::{FixedBytes, B256};
+use serde::{Deserialize, Serialize};
+use ssz::{Decode as _, DecodeError};
+use ssz_derive::{Decode, Encode};
+use ssz_types::{BitVector, FixedVector, VariableList};
+use superstruct::superstruct;
+use tree_hash::TreeHash;
+use tree_hash_derive::TreeHash;
+use typenum::Unsigned;
+
+use super::{
+    bls::{PublicKey, Signature},
+    gindex_to_index_and_depth, BeaconBlockHeader, ExecutionPayloadHeader, ForkName, Forks, SyncCommittee,
+};
+use crate::helios::{
+    proof::{historical_summary_index, merkle_branch, BlockRootsProof, HistoricalSummaryProof},
+    spec::ConsensusSpec,
+};
+
+type HistoricalRootsLimit = typenum::U16777216;
+type ValidatorRegistryLimit = typenum::U1099511627776;
+type PendingDepositsLimit = typenum::U134217728;
+
+/// Offset of `block_roots` in the SSZ encoded beacon state, the fields before it are fixed size in
+/// every fork.
+const BLOCK_ROOTS_OFFSET: usize = 8 + 32 + 8 + 16 + 112;
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct StateFork {
+    pub previous_version: FixedBytes<4>,
+    pub current_version: FixedBytes<4>,
+    pub epoch: u64,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct Eth1Data {
+    pub deposit_root: B256,
+    pub deposit_count: u64,
+    pub block_hash: B256,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct Validator {
+    pub pubkey: PublicKey,
+    pub withdrawal_credentials: B256,
+    pub effective_balance: u64,
+    pub slashed: bool,
+    pub activation_eligibility_epoch: u64,
+    pub activation_epoch: u64,
+    pub exit_epoch: u64,
+    pub withdrawable_epoch: u64,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct Checkpoint {
+    pub epoch: u64,
+    pub root: B256,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct HistoricalSummary {
+    pub block_summary_root: B256,
+    pub state_summary_root: B256,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct PendingDeposit {
+    pub pubkey: PublicKey,
+    pub withdrawal_credentials: B256,
+    pub amount: u64,
+    pub signature: Signature,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct PendingPartialWithdrawal {
+    pub validator_index: u64,
+    pub amount: u64,
+    pub withdrawable_epoch: u64,
+}
+
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash, PartialEq)]
+pub struct PendingConsolidation {
+    pub source_index: u64,
+    pub target_index: u64,
+}
+
+/// Beacon state, only decoded by the agent to build ancestry proofs.
+#[superstruct(
+    variants(Deneb, Electra, Fulu),
+    variant_attributes(derive(Debug, Clone, Default, Encode, Decode, TreeHash))
+)]
+#[derive(Debug, Clone)]
+pub struct BeaconState<S: ConsensusSpec> {
+    pub genesis_time: u64,
+    pub genesis_validators_root: B256,
+    pub slot: u64,
+    pub fork: StateFork,
+    pub latest_block_header: BeaconBlockHeader,
+    pub block_roots: FixedVector<B256, S::SlotsPerHistoricalRoot>,
+    pub state_roots: FixedVector<B256, S::SlotsPerHistoricalRoot>,
+    pub historical_roots: VariableList<B256, HistoricalRootsLimit>,
+    pub eth1_data: Eth1Data,
+    pub eth1_data_votes: VariableList<Eth1Data, S::SlotsPerEth1VotingPeriod>,
+    pub eth1_deposit_index: u64,
+    pub validators: VariableList<Validator, ValidatorRegistryLimit>,
+    pub balances: VariableList<u64, ValidatorRegistryLimit>,
+    pub randao_mixes: FixedVector<B256, S::EpochsPerHistoricalVector>,
+    pub slashings: FixedVector<u64, S::EpochsPerSlashingsVector>,
+    pub previous_epoch_participation: VariableList<u8, ValidatorRegistryLimit>,
+    pub current_epoch_participation: VariableList<u8, ValidatorRegistryLimit>,
+    pub justification_bits: BitVector<typenum::U4>,
+    pub previous_justified_checkpoint: Checkpoint,
+    pub current_justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub inactivity_scores: VariableList<u64, ValidatorRegistryLimit>,
+    pub current_sync_committee: SyncCommittee<S>,
+    pub next_sync_committee: SyncCommittee<S>,
+    pub latest_execution_payload_header: ExecutionPayloadHeader,
+    pub next_withdrawal_index: u64,
+    pub next_withdrawal_validator_index: u64,
+    pub historical_summaries: VariableList<HistoricalSummary, HistoricalRootsLimit>,
+    #[superstruct(only(Electra, Fulu))]
+    pub deposit_requests_start_index: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub deposit_balance_to_consume: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub exit_balance_to_consume: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub earliest_exit_epoch: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub consolidation_balance_to_consume: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub earliest_consolidation_epoch: u64,
+    #[superstruct(only(Electra, Fulu))]
+    pub pending_deposits: VariableList<PendingDeposit, PendingDepositsLimit>,
+    #[superstruct(only(Electra, Fulu))]
+    pub pending_partial_withdrawals: VariableList<PendingPartialWithdrawal, S::PendingPartialWithdrawalsLimit>,
+    #[superstruct(only(Electra, Fulu))]
+    pub pending_consolidations: VariableList<PendingConsolidation, S::PendingConsolidationsLimit>,
+    #[superstruct(only(Fulu))]
+    pub proposer_lookahead: FixedVector<u64, S::ProposerLookaheadSize>,
+}
+
+impl<S: ConsensusSpec> BeaconState<S> {
+    /// Decodes the container used at `fork`, states before Deneb are not supported.
+    pub fn from_ssz_bytes_for_fork(fork: ForkName, bytes: &[u8]) -> Result<Self, DecodeError> {
+        match fork {
+            ForkName::Deneb => BeaconStateDeneb::from_ssz_bytes(bytes).map(BeaconState::Deneb),
+            ForkName::Electra => BeaconStateElectra::from_ssz_bytes(bytes).map(BeaconState::Electra),
+            ForkName::Fulu => BeaconStateFulu::from_ssz_bytes(bytes).map(BeaconState::Fulu),
+            fork => Err(DecodeError::BytesInvalid(format!("unsupported beacon state fork: {:?}", fork))),
+        }
+    }
+
+    pub fn fork_name(&self) -> ForkName {
+        match self {
+            BeaconState::Deneb(_) => ForkName::Deneb,
+            BeaconState::Electra(_) => ForkName::Electra,
+            BeaconState::Fulu(_) => ForkName::Fulu,
+        }
+    }
+
+    /// Proves the root of the block at `slot` through `block_roots`, which covers the
+    /// `SLOTS_PER_HISTORICAL_ROOT` slots before the state.
+    pub fn block_roots_proof(&self, slot: u64) -> Option<BlockRootsProof> {
+        let slots_per_historical_root = S::slots_per_historical_root();
+        let state_slot = *self.slot();
+        if slot >= state_slot || state_slot - slot > slots_per_historical_root {
+            return None;
+        }
+
+        let depth = slots_per_historical_root.ilog2() as usize;
+        let index = (slot % slots_per_historical_root) as usize;
+        let mut branch = merkle_branch(self.block_roots(), depth, index);
+        branch.extend(self.field_branch(self.fork_name().ancestry_gindices().block_roots));
+
+        Some(BlockRootsProof { branch })
+    }
+
+    /// Proves the root of the block at `slot` of a summarized period through
+    /// `historical_summaries`, given the `block_roots` of that period.
+    pub fn historical_summary_proof(
+        &self,
+        slot: u64,
+        period_block_roots: &[B256],
+        forks: &Forks,
+    ) -> Option<HistoricalSummaryProof> {
+        let summaries = self.historical_summaries();
+        let summary_index = historical_summary_index::<S>(slot, forks)?;
+        let summary = summaries.get(summary_index)?.clone();
+
+        let period_block_roots =
+            FixedVector::<B256, S::SlotsPerHistoricalRoot>::new(period_block_roots.to_vec()).ok()?;
+        if period_block_roots.tree_hash_root() != summary.block_summary_root {
+            return None;
+        }
+
+        let slots_per_historical_root = S::slots_per_historical_root();
+        let block_root_branch = merkle_branch(
+            &period_block_roots,
+            slots_per_historical_root.ilog2() as usize,
+            (slot % slots_per_historical_root) as usize,
+        );
+
+        let leaves: Vec<B256> = summaries.iter().map(|summary| summary.tree_hash_root()).collect();
+        let mut summary_branch =
+            merkle_branch(&leaves, HistoricalRootsLimit::to_usize().ilog2() as usize, summary_index);
+        summary_branch.push(length_root(summaries.len()));
+        summary_branch.extend(self.field_branch(self.fork_name().ancestry_gindices().historical_summaries));
+
+        Some(HistoricalSummaryProof { block_root_branch, summary, summary_branch })
+    }
+
+    fn field_branch(&self, gindex: u64) -> Vec<B256> {
+        let (index, depth) = gindex_to_index_and_depth(gindex);
+        merkle_branch(&self.field_roots(), depth, index)
+    }
+
+    /// Roots of the state fields in container order, the leaves of the state root.
+    fn field_roots(&self) -> Vec<B256> {
+        let mut roots = vec![
+            self.genesis_time().tree_hash_root(),
+            self.genesis_validators_root().tree_hash_root(),
+            self.slot().tree_hash_root(),
+            self.fork().tree_hash_root(),
+            self.latest_block_header().tree_hash_root(),
+            self.block_roots().tree_hash_root(),
+            self.state_roots().tree_hash_root(),
+            self.historical_roots().tree_hash_root(),
+            self.eth1_data().tree_hash_root(),
+            self.eth1_data_votes().tree_hash_root(),
+            self.eth1_deposit_index().tree_hash_root(),
+            self.validators().tree_hash_root(),
+            self.balances().tree_hash_root(),
+            self.randao_mixes().tree_hash_root(),
+            self.slashings().tree_hash_root(),
+            self.previous_epoch_participation().tree_hash_root(),
+            self.current_epoch_participation().tree_hash_root(),
+            self.justification_bits().tree_hash_root(),
+            self.previous_justified_checkpoint().tree_hash_root(),
+            self.current_justified_checkpoint().tree_hash_root(),
+            self.finalized_checkpoint().tree_hash_root(),
+            self.inactivity_scores().tree_hash_root(),
+            self.current_sync_committee().tree_hash_root(),
+            self.next_sync_committee().tree_hash_root(),
+            self.latest_execution_payload_header().tree_hash_root(),
+            self.next_withdrawal_index().tree_hash_root(),
+            self.next_withdrawal_validator_index().tree_hash_root(),
+            self.historical_summaries().tree_hash_root(),
+        ];
+
+        roots.extend(self.deposit_requests_start_index().map(|value| value.tree_hash_root()));
+        roots.extend(self.deposit_balance_to_consume().map(|value| value.tree_hash_root()));
+        roots.extend(self.exit_balance_to_consume().map(|value| value.tree_hash_root()));
+        roots.extend(self.earliest_exit_epoch().map(|value| value.tree_hash_root()));
+        roots.extend(self.consolidation_balance_to_consume().map(|value| value.tree_hash_root()));
+        roots.extend(self.earliest_consolidation_epoch().map(|value| value.tree_hash_root()));
+        roots.extend(self.pending_deposits().map(|value| value.tree_hash_root()));
+        roots.extend(self.pending_partial_withdrawals().map(|value| value.tree_hash_root()));
+        roots.extend(self.pending_consolidations().map(|value| value.tree_hash_root()));
+        roots.extend(self.proposer_lookahead().map(|value| value.tree_hash_root()));
+
+        roots
+    }
+}
+
+/// Reads `block_roots` of an SSZ encoded state of any fork without decoding it, which is enough
+/// to prove blocks of the period before the state.
+pub fn read_block_roots<S: ConsensusSpec>(state: &[u8]) -> Option<Vec<B256>> {
+    let length = S::slots_per_historical_root() as usize * B256::len_bytes();
+    let bytes = state.get(BLOCK_ROOTS_OFFSET..BLOCK_ROOTS_OFFSET + length)?;
+
+    Some(bytes.chunks_exact(B256::len_bytes()).map(B256::from_slice).collect())
+}
+
+fn length_root(length: usize) -> B256 {
+    let mut root = B256::ZERO;
+    root[..8].copy_from_slice(&(length as u64).to_le_bytes());
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_block_roots, BeaconState, BeaconStateElectra, HistoricalSummary};
+    use crate::helios::{
+        proof::{is_ancestry_proof_valid, AncestryBranch},
+        spec::MinimalConsensusSpec,
+        types::{BeaconBlockHeader, Forks},
+    };
+    use alloy_primitives::B256;
+    use ssz::Encode;
+    use ssz_types::FixedVector;
+    use tree_hash::TreeHash;
+
+    type S = MinimalConsensusSpec;
+
+    fn header(slot: u64) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot,
+            proposer_index: slot,
+            parent_root: B256::repeat_byte(slot as u8),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ancestry_proofs() {
+        let forks: Forks = serde_json::from_str(
+            r#"[
+                { "name": "capella", "epoch": 0, "fork_version": "0x03000000" },
+                { "name": "electra", "epoch": 16, "fork_version": "0x05000000" }
+            ]"#,
+        )
+        .unwrap();
+
+        // The minimal preset keeps 64 block roots, slot 100 is of the summarized period 1.
+        let old = header(100);
+        let mut period_roots = vec![B256::ZERO; 64];
+        period_roots[100 % 64] = old.tree_hash_root();
+        let summary = HistoricalSummary {
+            block_summary_root: FixedVector::<B256, typenum::U64>::from(period_roots.clone()).tree_hash_root(),
+            state_summary_root: B256::repeat_byte(7),
+        };
+
+        let recent = header(180);
+        let mut state = BeaconStateElectra::<S> { slot: 200, ..Default::default() };
+        state.block_roots[180 % 64] = recent.tree_hash_root();
+        state.historical_summaries = vec![HistoricalSummary::default(), summary.clone()].into();
+
+        let anchor = BeaconBlockHeader { slot: 200, state_root: state.tree_hash_root(), ..Default::default() };
+        let state = BeaconState::Electra(state);
+
+        let proof = AncestryBranch::BlockRoots(state.block_roots_proof(180).unwrap());
+        assert!(is_ancestry_proof_valid::<S>(&recent, &anchor, &proof, &forks));
+        assert!(!is_ancestry_proof_valid::<S>(&header(181), &anchor, &proof, &forks));
+        assert!(state.block_roots_proof(100).is_none());
+
+        let proof = state.historical_summary_proof(100, &period_roots, &forks).unwrap();
+        assert_eq!(proof.summary, summary);
+        let proof = AncestryBranch::HistoricalSummary(proof);
+        assert!(is_ancestry_proof_valid::<S>(&old, &anchor, &proof, &forks));
+        assert!(!is_ancestry_proof_valid::<S>(&header(36), &anchor, &proof, &forks));
+        assert!(state.historical_summary_proof(100, &[B256::ZERO; 64], &forks).is_none());
+
+        let BeaconState::Electra(inner) = &state else { unreachable!() };
+        assert_eq!(read_block_roots::<S>(&inner.as_ssz_bytes()).unwrap(), state.block_roots().to_vec());
+    }
+}
@@ -15,24 +15,11 @@ pub fn compute_committee_sign_root(header: B256, fork_data_root: B256) -> B256 {
     compute_signing_root(header, domain)
 }
 
-pub fn calculate_fork_version<S: ConsensusSpec>(forks: &Forks, slot: u64) -> FixedVector<u8, typenum::U4> {
+pub fn calculate_fork_version<S: ConsensusSpec>(forks: &Forks, slot: u64) -> Option<FixedVector<u8, typenum::U4>> {
     let epoch = slot / S::slots_per_epoch();
+    let fork = forks.at_epoch(epoch)?;
 
-    let version = if epoch >= forks.electra.epoch {
-        forks.electra.fork_version
-    } else if epoch >= forks.deneb.epoch {
-        forks.deneb.fork_version
-    } else if epoch >= forks.capella.epoch {
-        forks.capella.fork_version
-    } else if epoch >= forks.bellatrix.epoch {
-        forks.bellatrix.fork_version
-    } else if epoch >= forks.altair.epoch {
-        forks.altair.fork_version
-    } else {
-        forks.genesis.fork_version
-    };
-
-    FixedVector::from(version.as_slice().to_vec())
+    Some(FixedVector::from(fork.fork_version.as_slice().to_vec()))
 }
 
 pub fn compute_fork_data_root(current_version: FixedVector<u8, typenum::U4>, genesis_validator_root: B256) -> B256 {
@@ -1,6 +1,8 @@
 pub mod checkpoint;
 pub mod config;
 pub mod consensus;
+pub mod execution;
+pub mod fees;
 pub mod helios;
 pub mod payload;
 
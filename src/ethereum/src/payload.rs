@@ -12,6 +12,10 @@ pub struct LightClientStoreDiff<S: ConsensusSpec> {
     pub current_sync_committee: Option<SyncCommittee<S>>,
     pub next_sync_committee: Option<Option<SyncCommittee<S>>>,
     pub best_valid_update: Option<Option<GenericUpdate<S>>>,
+    #[serde(default)]
+    pub previous_max_active_participants: Option<u64>,
+    #[serde(default)]
+    pub current_max_active_participants: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -40,6 +44,14 @@ pub fn patch_store<S: ConsensusSpec>(store: &mut LightClientStore<S>, diff: Ligh
     if let Some(best_valid_update) = diff.best_valid_update {
         store.best_valid_update = best_valid_update;
     }
+
+    if let Some(previous_max_active_participants) = diff.previous_max_active_participants {
+        store.previous_max_active_participants = previous_max_active_participants;
+    }
+
+    if let Some(current_max_active_participants) = diff.current_max_active_participants {
+        store.current_max_active_participants = current_max_active_participants;
+    }
 }
 
 pub fn diff_store<S: ConsensusSpec>(
@@ -74,6 +86,16 @@ pub fn diff_store<S: ConsensusSpec>(
         update_required = true;
     }
 
+    if store.previous_max_active_participants != reference.previous_max_active_participants {
+        update.previous_max_active_participants = Some(reference.previous_max_active_participants);
+        update_required = true;
+    }
+
+    if store.current_max_active_participants != reference.current_max_active_participants {
+        update.current_max_active_participants = Some(reference.current_max_active_participants);
+        update_required = true;
+    }
+
     if update_required {
         Some(update)
     } else {
@@ -1,25 +1,37 @@
 use crate::{
     chain::{Chain, GenericChain, GenericChainBlueprint},
-    ethereum::EthereumChain,
+    ethereum::{backfill_headers, prove_ancestry, EthereumChain},
     outcalls::OutcallsChain,
 };
-use ic_lightclient_ethereum::helios::spec::MainnetConsensusSpec;
-use ic_lightclient_wire::ethereum::{lightclient, outcalls};
+use anyhow::{bail, Result};
+use ic_lightclient_ethereum::helios::spec::{MainnetConsensusSpec, MinimalConsensusSpec};
+use ic_lightclient_wire::{
+    ethereum::{lightclient, outcalls},
+    ChainId,
+};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 pub struct EthereumMainnetBlueprint;
 
 impl GenericChainBlueprint for EthereumMainnetBlueprint {
-    const CHAIN_UID: u16 = 1;
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_MAINNET;
     type WireProtocol = lightclient::EthereumWireProtocol<MainnetConsensusSpec>;
-    type StateMachine = EthereumChain;
+    type StateMachine = EthereumChain<MainnetConsensusSpec>;
+}
+
+pub struct EthereumDevnetBlueprint;
+
+impl GenericChainBlueprint for EthereumDevnetBlueprint {
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_DEVNET;
+    type WireProtocol = lightclient::EthereumWireProtocol<MinimalConsensusSpec>;
+    type StateMachine = EthereumChain<MinimalConsensusSpec>;
 }
 
 pub struct EthereumHoleskyBlueprint;
 
 impl GenericChainBlueprint for EthereumHoleskyBlueprint {
-    const CHAIN_UID: u16 = 17000;
+    const CHAIN_UID: ChainId = ChainId::ETHEREUM_HOLESKY;
     type WireProtocol = outcalls::OutcallsWireProtocol;
     type StateMachine = OutcallsChain;
 }
@@ -28,10 +40,29 @@ fn build_chain<B: GenericChainBlueprint + 'static>() -> Arc<Mutex<dyn Chain + Se
     Arc::new(Mutex::new(GenericChain::<B>::new()))
 }
 
-pub fn build_chain_from_uid(uid: u16) -> Arc<Mutex<dyn Chain + Send>> {
+pub fn build_chain_from_uid(uid: ChainId) -> Arc<Mutex<dyn Chain + Send>> {
     match uid {
         EthereumMainnetBlueprint::CHAIN_UID => build_chain::<EthereumMainnetBlueprint>(),
         EthereumHoleskyBlueprint::CHAIN_UID => build_chain::<EthereumHoleskyBlueprint>(),
+        EthereumDevnetBlueprint::CHAIN_UID => build_chain::<EthereumDevnetBlueprint>(),
         _ => panic!("invalid chain uid received"),
     }
 }
+
+/// Ancestry proofs depend on the consensus spec of the chain.
+pub async fn prove_ancestry_from_uid(uid: ChainId, slot: u64) -> Result<()> {
+    match uid {
+        EthereumMainnetBlueprint::CHAIN_UID => prove_ancestry::<MainnetConsensusSpec>(uid, slot).await,
+        EthereumDevnetBlueprint::CHAIN_UID => prove_ancestry::<MinimalConsensusSpec>(uid, slot).await,
+        _ => bail!("Chain {} does not support ancestry proofs.", uid),
+    }
+}
+
+/// Header chains are sent in the update payloads of the chain's wire protocol.
+pub async fn backfill_headers_from_uid(uid: ChainId, from_block: u64) -> Result<()> {
+    match uid {
+        EthereumMainnetBlueprint::CHAIN_UID => backfill_headers::<MainnetConsensusSpec>(uid, from_block).await,
+        EthereumDevnetBlueprint::CHAIN_UID => backfill_headers::<MinimalConsensusSpec>(uid, from_block).await,
+        _ => bail!("Chain {} does not support header backfill.", uid),
+    }
+}
@@ -1,20 +1,26 @@
 use crate::chain::traits::StateMachine;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
-use ic_lightclient_wire::{StatePayloadParser, UpdatePayloadMarshaller, WireProtocol};
+use ic_lightclient_oc_utils::IcpAgent;
+use ic_lightclient_wire::{
+    state_hash, ChainId, ChainStateDelta, ChainStateDigest, SupportedWireVersions, UpdatePayloadMarshaller,
+    WireProtocol,
+};
 
 #[async_trait]
 pub trait Chain {
     async fn init(&mut self, config: Vec<u8>) -> Result<()>;
+    /// Fails when the canister can't parse the payloads this agent writes for the chain.
+    fn check_wire_versions(&self, versions: &SupportedWireVersions) -> Result<()>;
     async fn get_updates(
         &mut self,
-        state_parser: &StatePayloadParser,
+        digest: &ChainStateDigest,
         updates_marshaller: &mut UpdatePayloadMarshaller,
     ) -> Result<()>;
 }
 
 pub trait GenericChainBlueprint {
-    const CHAIN_UID: u16;
+    const CHAIN_UID: ChainId;
     type WireProtocol: WireProtocol;
     type StateMachine: StateMachine<
         Config = <Self::WireProtocol as WireProtocol>::Config,
@@ -23,13 +29,71 @@ pub trait GenericChainBlueprint {
     >;
 }
 
+/// Last fetched canister state of the chain.
+struct CachedState<P> {
+    version: u64,
+    hash: Vec<u8>,
+    state: P,
+}
+
+type ExtractStatePayload<B> = <<B as GenericChainBlueprint>::WireProtocol as WireProtocol>::StatePayload;
+
 pub struct GenericChain<Blueprint: GenericChainBlueprint> {
     state_machine: Blueprint::StateMachine,
+    cached_state: Option<CachedState<ExtractStatePayload<Blueprint>>>,
 }
 
 impl<Blueprint: GenericChainBlueprint> GenericChain<Blueprint> {
     pub fn new() -> Self {
-        Self { state_machine: Blueprint::StateMachine::new() }
+        Self { state_machine: Blueprint::StateMachine::new(), cached_state: None }
+    }
+
+    /// Fetches only what changed since the cached version, and nothing when the digest matches.
+    /// When the delta can't be fetched or applied, the cached state is dropped and fetched in full.
+    async fn sync_state(&mut self, digest: &ChainStateDigest) -> Result<ExtractStatePayload<Blueprint>> {
+        if let Some(cached) = &self.cached_state {
+            if cached.version == digest.version && cached.hash == digest.hash {
+                return Ok(cached.state.clone());
+            }
+        }
+
+        if let Some(cached) = self.cached_state.take() {
+            let since_version = cached.version;
+            match self.fetch_state(Some(cached)).await {
+                Ok(state) => return Ok(state),
+                Err(e) => println!(
+                    "Chain {}: failed to sync state since version {}, fetching it in full: {:?}",
+                    Blueprint::CHAIN_UID,
+                    since_version,
+                    e
+                ),
+            }
+        }
+
+        self.fetch_state(None).await
+    }
+
+    /// Fetches the state, as a delta on top of `cached` if set, and caches it once its hash
+    /// matches the canister's.
+    async fn fetch_state(
+        &mut self,
+        cached: Option<CachedState<ExtractStatePayload<Blueprint>>>,
+    ) -> Result<ExtractStatePayload<Blueprint>> {
+        let since_version = cached.as_ref().map(|cached| cached.version);
+        let delta = IcpAgent::get_chain_state(Blueprint::CHAIN_UID, since_version).await?;
+        let delta = ChainStateDelta::parse(delta.as_slice())?;
+        let state = delta.apply::<Blueprint::WireProtocol>(cached.map(|cached| cached.state))?;
+
+        if state_hash::<Blueprint::WireProtocol>(&state)? != delta.hash {
+            bail!(
+                "Chain {} state doesn't match the canister's hash at version {}",
+                Blueprint::CHAIN_UID,
+                delta.version
+            );
+        }
+
+        self.cached_state = Some(CachedState { version: delta.version, hash: delta.hash, state: state.clone() });
+        Ok(state)
     }
 }
 
@@ -41,15 +105,23 @@ impl<Blueprint: GenericChainBlueprint> Chain for GenericChain<Blueprint> {
         Ok(())
     }
 
+    fn check_wire_versions(&self, versions: &SupportedWireVersions) -> Result<()> {
+        versions.ensure_supported::<Blueprint::WireProtocol>(Blueprint::CHAIN_UID)
+    }
+
     async fn get_updates(
         &mut self,
-        state_parser: &StatePayloadParser,
+        digest: &ChainStateDigest,
         updates_marshaller: &mut UpdatePayloadMarshaller,
     ) -> Result<()> {
-        let state = state_parser.state::<Blueprint::WireProtocol>(Blueprint::CHAIN_UID)?;
+        let state = self.sync_state(digest).await?;
         let updates = self.state_machine.get_updates(state).await?;
         if updates.len() > 0 {
             updates_marshaller.updates::<Blueprint::WireProtocol>(Blueprint::CHAIN_UID, updates)?;
+            // The synced state may be newer than the digest, if it changed in between.
+            if let Some(cached) = &self.cached_state {
+                updates_marshaller.based_on(Blueprint::CHAIN_UID, cached.version, cached.hash.clone());
+            }
         }
 
         Ok(())
@@ -1,10 +1,11 @@
 use crate::chain::chain::Chain;
+use ic_lightclient_wire::ChainId;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
 
 #[derive(Clone)]
 pub struct ChainManager {
-    pub chains: HashMap<u16, Arc<Mutex<dyn Chain + Send>>>,
+    pub chains: HashMap<ChainId, Arc<Mutex<dyn Chain + Send>>>,
 }
 
 impl ChainManager {
@@ -13,15 +14,15 @@ impl ChainManager {
         Self { chains }
     }
 
-    pub fn get(&self, uid: &u16) -> Option<Arc<Mutex<dyn Chain + Send>>> {
+    pub fn get(&self, uid: &ChainId) -> Option<Arc<Mutex<dyn Chain + Send>>> {
         self.chains.get(uid).map(|v| v.clone())
     }
 
-    pub fn set(&mut self, uid: u16, chain: Arc<Mutex<dyn Chain + Send>>) {
+    pub fn set(&mut self, uid: ChainId, chain: Arc<Mutex<dyn Chain + Send>>) {
         self.chains.insert(uid, chain);
     }
 
-    pub fn list(&self) -> Vec<u16> {
+    pub fn list(&self) -> Vec<ChainId> {
         self.chains.keys().map(|k| k.clone()).collect()
     }
 }
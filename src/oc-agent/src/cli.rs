@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use ic_lightclient_wire::ChainId;
 use std::sync::OnceLock;
 
 static INNER: OnceLock<CliArgs> = OnceLock::new();
@@ -9,6 +10,22 @@ static INNER: OnceLock<CliArgs> = OnceLock::new();
 struct CliArgs {
     #[arg(short, long, default_value = "oraclekit.toml")]
     config_file: String,
+    /// Prove the inclusion of a transaction to the canister and exit.
+    #[arg(long)]
+    prove_transaction: Option<String>,
+    /// Prove a block older than the canister's header history by its beacon slot and exit.
+    #[arg(long)]
+    prove_ancestry: Option<u64>,
+    /// Backfill the execution headers from this block number to the canister's finalized block
+    /// and exit.
+    #[arg(long)]
+    backfill: Option<u64>,
+    /// Send update payloads as JSON instead of bincode, for debugging.
+    #[arg(long)]
+    json_payloads: bool,
+    /// Chain of the transaction or block to prove, or of the headers to backfill.
+    #[arg(long, default_value_t = 1)]
+    chain: u64,
 }
 
 pub struct Cli {}
@@ -26,4 +43,24 @@ impl Cli {
     pub fn config_file() -> String {
         INNER.get().unwrap().config_file.clone()
     }
+
+    pub fn prove_transaction() -> Option<String> {
+        INNER.get().unwrap().prove_transaction.clone()
+    }
+
+    pub fn prove_ancestry() -> Option<u64> {
+        INNER.get().unwrap().prove_ancestry
+    }
+
+    pub fn backfill() -> Option<u64> {
+        INNER.get().unwrap().backfill
+    }
+
+    pub fn json_payloads() -> bool {
+        INNER.get().unwrap().json_payloads
+    }
+
+    pub fn chain() -> ChainId {
+        INNER.get().unwrap().chain.into()
+    }
 }
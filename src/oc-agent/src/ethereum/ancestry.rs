@@ -0,0 +1,54 @@
+use crate::util::ConsensusApi;
+use anyhow::{anyhow, Result};
+use ic_lightclient_ethereum::{
+    config::EthereumConfigPopulated,
+    helios::{
+        proof::{AncestryBranch, AncestryProof},
+        spec::ConsensusSpec,
+        types::{read_block_roots, BeaconState},
+    },
+};
+use ic_lightclient_oc_utils::{Commitment, IcpAgent};
+use ic_lightclient_wire::ChainId;
+use serde::de::DeserializeOwned;
+use ssz::Encode;
+
+/// Proves the block at `slot` to be an ancestor of the canister's finalized header and has the
+/// canister verify it. Blocks of the last `SLOTS_PER_HISTORICAL_ROOT` slots are proven through
+/// the `block_roots` of the finalized state, older ones through its `historical_summaries`, which
+/// needs the state at the end of the block's period.
+pub async fn prove_ancestry<S: ConsensusSpec + DeserializeOwned>(chain: ChainId, slot: u64) -> Result<()> {
+    let config = IcpAgent::get_canister_config(chain).await?;
+    let config: EthereumConfigPopulated = serde_json::from_slice(config.as_slice())?;
+    let consensus_api = ConsensusApi::<S>::new(config.consensus_api);
+
+    let anchor_slot = IcpAgent::get_latest_block(chain, Commitment::Finalized).await?.beacon_slot;
+    let block_root = consensus_api.block_root(slot).await?;
+    let header = consensus_api.bootstrap(block_root).await?.header().clone();
+
+    let (fork, state) = consensus_api.state(anchor_slot).await?;
+    let state = BeaconState::<S>::from_ssz_bytes_for_fork(fork, &state)
+        .map_err(|e| anyhow!("Failed to decode beacon state at slot {}: {:?}", anchor_slot, e))?;
+
+    let branch = match state.block_roots_proof(slot) {
+        Some(proof) => AncestryBranch::BlockRoots(proof),
+        None => {
+            let slots_per_historical_root = S::slots_per_historical_root();
+            let period_end = (slot / slots_per_historical_root + 1) * slots_per_historical_root;
+            let (_, period_state) = consensus_api.state(period_end).await?;
+            let period_block_roots = read_block_roots::<S>(&period_state)
+                .ok_or(anyhow!("Beacon state at slot {} is too short.", period_end))?;
+
+            let proof = state
+                .historical_summary_proof(slot, &period_block_roots, &config.forks)
+                .ok_or(anyhow!("Slot {} is not summarized in the state at slot {}.", slot, anchor_slot))?;
+            AncestryBranch::HistoricalSummary(proof)
+        }
+    };
+
+    let proof = AncestryProof { header, anchor_slot, branch };
+    let header = IcpAgent::verify_ancestry(chain, proof.as_ssz_bytes()).await?;
+    println!("Chain {}: block at slot {} verified: {}", chain, slot, header);
+
+    Ok(())
+}
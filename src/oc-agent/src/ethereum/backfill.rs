@@ -0,0 +1,63 @@
+use crate::{
+    update_payload_marshaller,
+    util::{ExecutionApi, ExecutionRequestBatch},
+};
+use anyhow::{bail, Result};
+use ic_lightclient_ethereum::{config::EthereumConfigPopulated, execution::HeaderChain, helios::spec::ConsensusSpec};
+use ic_lightclient_oc_utils::{Commitment, IcpAgent};
+use ic_lightclient_wire::{
+    ethereum::lightclient::{EthereumWireProtocol, LightClientUpdatePayload},
+    ChainId,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+const MAX_BACKFILL_HEADERS: u64 = 256;
+
+/// Backfills the execution headers from `from_block` up to the canister's finalized block. Runs
+/// are submitted from the newest on, each ending at the oldest header of the previous run so the
+/// canister can link it to a hash it already verified.
+pub async fn backfill_headers<S: ConsensusSpec + Serialize + DeserializeOwned>(
+    chain: ChainId,
+    from_block: u64,
+) -> Result<()> {
+    let config = IcpAgent::get_canister_config(chain).await?;
+    let config: EthereumConfigPopulated = serde_json::from_slice(config.as_slice())?;
+    let execution_api = ExecutionApi::new(config.execution_api);
+
+    let mut end = IcpAgent::get_latest_block(chain, Commitment::Finalized)
+        .await?
+        .execution_block_number;
+    if from_block > end {
+        bail!("Block {} is newer than the finalized block {}.", from_block, end);
+    }
+
+    loop {
+        let start = end.saturating_sub(MAX_BACKFILL_HEADERS - 1).max(from_block);
+
+        let mut batch = ExecutionRequestBatch::new();
+        let mut receipts = (start..=end)
+            .map(|block_number| batch.block_header_by_number(block_number))
+            .collect::<Result<Vec<_>>>()?;
+        execution_api.batch_request(batch).await?;
+        let headers = receipts
+            .iter_mut()
+            .map(|receipt| receipt.get().map(|header| header.inner))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut updates = update_payload_marshaller();
+        updates.updates::<EthereumWireProtocol<S>>(
+            chain,
+            vec![LightClientUpdatePayload::HeaderChain(HeaderChain::build(&headers))],
+        )?;
+        let reports = IcpAgent::update_canister_state(updates.build_upload()?).await?;
+        if let Some(rejected) = reports.iter().flat_map(|report| &report.rejected).next() {
+            bail!("Headers {} to {} rejected: {}", start, end, rejected.error);
+        }
+        println!("Chain {}: backfilled headers {} to {}", chain, start, end);
+
+        if start == from_block {
+            return Ok(());
+        }
+        end = start;
+    }
+}
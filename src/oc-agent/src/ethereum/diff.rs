@@ -1,77 +1,111 @@
 use anyhow::{anyhow, Result};
 use ic_lightclient_ethereum::{
     helios::{
+        consensus::calc_sync_period,
         spec::ConsensusSpec,
-        types::{Bootstrap, LightClientStore},
+        types::{Bootstrap, GenericUpdate, LightClientHeader},
     },
     EthereumLightClientConsensus,
 };
+use ic_lightclient_utils::CircularQueue;
 use ic_lightclient_wire::ethereum::lightclient::{LightClientStatePayload, LightClientUpdatePayload};
+use std::{collections::BTreeMap, time::SystemTime};
+
+const MAX_BUFFERED_UPDATES: usize = 128;
+const MAX_UPDATES_PER_BATCH: usize = 8;
 
 pub struct EthereumStateDiff<S: ConsensusSpec> {
     bootstrap: Option<Bootstrap<S>>,
+    /// Updates carrying the next sync committee, by the period of their attested header. A canister
+    /// which missed one can't follow the chain past that period, so they are only dropped once the
+    /// canister finalized a later period.
+    period_updates: BTreeMap<u64, GenericUpdate<S>>,
+    /// Most recent head updates.
+    updates: CircularQueue<GenericUpdate<S>>,
 }
 
 impl<S: ConsensusSpec> Default for EthereumStateDiff<S> {
     fn default() -> Self {
-        Self { bootstrap: None }
+        Self { bootstrap: None, period_updates: BTreeMap::new(), updates: CircularQueue::new(MAX_BUFFERED_UPDATES) }
     }
 }
 
 impl<S: ConsensusSpec> EthereumStateDiff<S> {
     pub fn add_bootstrap(&mut self, bootstrap: Bootstrap<S>) {
         self.bootstrap = Some(bootstrap);
+        self.period_updates.clear();
+        self.updates.clear();
     }
 
+    pub fn add_update(&mut self, update: GenericUpdate<S>) {
+        if update.next_sync_committee.is_some() {
+            let period = calc_sync_period::<S>(update.attested_header.beacon.slot);
+            self.period_updates.insert(period, update);
+        } else {
+            self.updates.queue(update);
+        }
+    }
+
+    /// Returns the updates to send along with the optimistic header the canister will end up
+    /// with after applying them.
     pub fn get_diff_updates(
-        &self,
+        &mut self,
         canister_state: &LightClientStatePayload<S>,
         store: &EthereumLightClientConsensus<S>,
-    ) -> Result<Vec<LightClientUpdatePayload<S>>> {
-        match &canister_state {
-            LightClientStatePayload::Bootstrap(_state) => {
-                // if root != bootstrap_update.header.beacon.tree_hash_root() {
-                //     panic!("Bootstrap block hash mismatch, {:?}", bootstrap_update.header);
-                // }
+    ) -> Result<(Vec<LightClientUpdatePayload<S>>, LightClientHeader)> {
+        // The canister verifies every update it receives, so instead of sending a diff of the
+        // store, replay the buffered updates on a replica of the canister state and only send
+        // the ones which move it forward.
+        let mut replica = EthereumLightClientConsensus::new(store.get_config().clone());
+        let mut updates = vec![];
 
+        match &canister_state {
+            LightClientStatePayload::Bootstrap(_) => {
                 println!("Received request for bootstrap!");
-                return self.get_diff_updates_for_bootstrap(store);
+
+                let bootstrap = self.bootstrap.as_ref().ok_or(anyhow!("Bootstrap update not found"))?;
+                replica.bootstrap(bootstrap)?;
+                updates.push(LightClientUpdatePayload::Bootstrap(bootstrap.clone()));
             }
 
             LightClientStatePayload::Active(state) => {
                 let slot = state.optimistic_header.beacon.slot;
                 println!("Received request for slot: {}!", slot);
 
-                return Ok(self.get_diff_updates_for_active(&state, &store));
+                let finalized_period = calc_sync_period::<S>(state.finalized_header.beacon.slot);
+                self.period_updates = self.period_updates.split_off(&finalized_period);
+                replica.load_state(canister_state.clone());
             }
         }
-    }
 
-    fn get_diff_updates_for_bootstrap(
-        &self,
-        store: &EthereumLightClientConsensus<S>,
-    ) -> Result<Vec<LightClientUpdatePayload<S>>> {
-        let bootstrap = self.bootstrap.as_ref().ok_or(anyhow!("Bootstrap update not found"))?;
-        let mut updates = vec![LightClientUpdatePayload::Bootstrap(bootstrap.clone())];
+        let current_time_ns = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+        let current_time_ns = current_time_ns.as_nanos().try_into()?;
 
-        if let Some(diff) = store.diff(&LightClientStore::default()) {
-            updates.push(LightClientUpdatePayload::Update(diff));
-        }
+        let mut buffered: Vec<&GenericUpdate<S>> = self.period_updates.values().chain(self.updates.iter()).collect();
+        buffered.sort_by_key(|update| update.signature_slot);
 
-        Ok(updates)
-    }
+        for update in buffered {
+            if updates.len() >= MAX_UPDATES_PER_BATCH {
+                break;
+            }
 
-    fn get_diff_updates_for_active(
-        &self,
-        canister_store: &LightClientStore<S>,
-        store: &EthereumLightClientConsensus<S>,
-    ) -> Vec<LightClientUpdatePayload<S>> {
-        let mut updates = vec![];
+            // Updates behind the replica's head usually fail as the canister already applied them,
+            // a failing update ahead of it means the canister can't follow this agent.
+            let reference = replica.get_store().clone();
+            let is_behind = update.attested_header.beacon.slot <= reference.optimistic_header.beacon.slot;
+            if let Err(error) = replica.update(update, current_time_ns) {
+                if !is_behind {
+                    println!("Skipping buffered update of slot {}: {}", update.signature_slot, error);
+                }
+                continue;
+            }
 
-        if let Some(diff) = store.diff(canister_store) {
-            updates.push(LightClientUpdatePayload::Update(diff));
+            if replica.diff(&reference).is_some() {
+                updates.push(LightClientUpdatePayload::GenericUpdate(update.clone()));
+            }
         }
 
-        updates
+        let header = replica.get_store().optimistic_header.clone();
+        Ok((updates, header))
     }
 }
@@ -1,44 +1,56 @@
+mod ancestry;
+mod backfill;
 mod diff;
+mod transaction;
 
 use crate::{
     chain::StateMachine,
     util::{ConsensusApi, ExecutionApi},
 };
+use alloy_consensus::ReceiptEnvelope;
 use alloy_primitives::B256;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use diff::EthereumStateDiff;
 use ic_lightclient_ethereum::{
     config::EthereumConfigPopulated,
+    execution::{AccountProof, LogFilter, ReceiptProof, StorageProof, WatchedAccount},
     helios::{
         consensus::{calc_sync_period, expected_current_slot},
-        spec::MainnetConsensusSpec,
-        types::{Forks, GenericUpdate, Update},
+        spec::ConsensusSpec,
+        types::{ExecutionPayloadHeader, Forks, GenericUpdate, Update},
     },
     EthereumLightClientConsensus,
 };
 use ic_lightclient_wire::ethereum::lightclient::{Block, LightClientStatePayload, LightClientUpdatePayload};
+use serde::{de::DeserializeOwned, Serialize};
 use std::time::SystemTime;
 
+pub use ancestry::prove_ancestry;
+pub use backfill::backfill_headers;
+pub use transaction::prove_transaction;
+
 const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u8 = 128;
 
 #[derive(Default)]
-pub struct EthereumChain {
-    light_client_store: EthereumLightClientConsensus<MainnetConsensusSpec>,
+pub struct EthereumChain<S: ConsensusSpec> {
+    light_client_store: EthereumLightClientConsensus<S>,
     genesis_time: u64,
     genesis_validator_root: B256,
     forks: Forks,
     last_updated_time_sec: u64,
-    state_differ: EthereumStateDiff<MainnetConsensusSpec>,
-    consensus_api: ConsensusApi,
+    state_differ: EthereumStateDiff<S>,
+    consensus_api: ConsensusApi<S>,
     execution_api: ExecutionApi,
+    watched_accounts: Vec<WatchedAccount>,
+    watched_logs: Vec<LogFilter>,
 }
 
 #[async_trait]
-impl StateMachine for EthereumChain {
+impl<S: ConsensusSpec + Serialize + DeserializeOwned> StateMachine for EthereumChain<S> {
     type Config = EthereumConfigPopulated;
-    type CanisterStatePayload = LightClientStatePayload<MainnetConsensusSpec>;
-    type CanisterUpdatePayload = LightClientUpdatePayload<MainnetConsensusSpec>;
+    type CanisterStatePayload = LightClientStatePayload<S>;
+    type CanisterUpdatePayload = LightClientUpdatePayload<S>;
 
     fn new() -> Self {
         Self::default()
@@ -51,6 +63,8 @@ impl StateMachine for EthereumChain {
         self.genesis_time = config.genesis_time;
         self.genesis_validator_root = config.genesis_validator_root;
         self.forks = config.forks.clone();
+        self.watched_accounts = config.watched_accounts.clone();
+        self.watched_logs = config.watched_logs.clone();
         let checkpoint = config.checkpoint.checkpoint_block_root;
 
         let bootstrap = self.consensus_api.bootstrap(checkpoint).await?;
@@ -64,17 +78,19 @@ impl StateMachine for EthereumChain {
 
     async fn get_updates(
         &mut self,
-        canister_state: LightClientStatePayload<MainnetConsensusSpec>,
-    ) -> Result<Vec<LightClientUpdatePayload<MainnetConsensusSpec>>> {
+        canister_state: LightClientStatePayload<S>,
+    ) -> Result<Vec<LightClientUpdatePayload<S>>> {
         self.check_and_sync().await?;
 
         // check for next sync committee
 
-        let mut updates = self.state_differ.get_diff_updates(&canister_state, &self.light_client_store)?;
+        let (mut updates, header) = self.state_differ.get_diff_updates(&canister_state, &self.light_client_store)?;
 
         if updates.len() > 0 {
-            let block = self.get_latest_block_update().await?;
+            let block = self.get_block_update(&header.execution).await?;
             updates.push(block);
+            updates.extend(self.get_account_proofs(header.execution.block_number).await?);
+            updates.extend(self.get_receipt_proofs(&header.execution).await?);
 
             Ok(updates)
         } else {
@@ -83,32 +99,99 @@ impl StateMachine for EthereumChain {
     }
 }
 
-impl EthereumChain {
-    async fn get_latest_block_update(&self) -> Result<LightClientUpdatePayload<MainnetConsensusSpec>> {
-        let latest_block_num = self.execution_api.latest_block_number().await?;
-        let block_hash = self.execution_api.block_header_by_number(latest_block_num).await?;
-        let block_hash = block_hash.hash.to_string();
-        let latest_block_num = latest_block_num.try_into()?;
-        let base_gas_fee = self.execution_api.base_gas_fee().await?;
-        let base_gas_fee = base_gas_fee.try_into()?;
+impl<S: ConsensusSpec + Serialize + DeserializeOwned> EthereumChain<S> {
+    async fn get_block_update(&self, execution: &ExecutionPayloadHeader) -> Result<LightClientUpdatePayload<S>> {
+        let block_hash = execution.block_hash.to_string();
+        let base_gas_fee = execution.base_fee_per_gas.try_into()?;
         let max_priority_fee = self.execution_api.max_priority_fee().await?;
         let max_priority_fee = max_priority_fee.try_into()?;
 
         Ok(LightClientUpdatePayload::Block(Block {
-            block_num: latest_block_num,
+            block_num: execution.block_number.into(),
             block_hash,
             base_gas_fee,
             max_priority_fee,
+            timestamp: execution.timestamp,
         }))
     }
 
+    /// Proofs are taken at the block of the optimistic header the canister ends up with, so they
+    /// can be verified against its state root.
+    async fn get_account_proofs(&self, block_number: u64) -> Result<Vec<LightClientUpdatePayload<S>>> {
+        let mut updates = vec![];
+
+        for account in &self.watched_accounts {
+            let proof = self
+                .execution_api
+                .proof(account.address, &account.storage_slots, block_number)
+                .await?;
+
+            let storage_proof = proof
+                .storage_proof
+                .into_iter()
+                .map(|storage| StorageProof { slot: storage.key.as_b256(), value: storage.value, proof: storage.proof })
+                .collect();
+
+            updates.push(LightClientUpdatePayload::AccountProof(AccountProof {
+                block_number,
+                address: proof.address,
+                nonce: proof.nonce,
+                balance: proof.balance,
+                storage_hash: proof.storage_hash,
+                code_hash: proof.code_hash,
+                account_proof: proof.account_proof,
+                storage_proof,
+            }));
+        }
+
+        Ok(updates)
+    }
+
+    /// Proves the receipts of the block which carry watched logs.
+    async fn get_receipt_proofs(&self, execution: &ExecutionPayloadHeader) -> Result<Vec<LightClientUpdatePayload<S>>> {
+        if self.watched_logs.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let receipts: Vec<ReceiptEnvelope> = self
+            .execution_api
+            .block_receipts(execution.block_number)
+            .await?
+            .into_iter()
+            .map(|receipt| receipt.into_primitives_receipt().into_inner())
+            .collect();
+
+        let indices: Vec<u64> = receipts
+            .iter()
+            .enumerate()
+            .filter(|(_, receipt)| {
+                receipt
+                    .logs()
+                    .iter()
+                    .any(|log| self.watched_logs.iter().any(|filter| filter.matches(log)))
+            })
+            .map(|(index, _)| index as u64)
+            .collect();
+
+        if indices.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let (receipts_root, proofs) = ReceiptProof::build(execution.block_number, &receipts, &indices);
+        if receipts_root != execution.receipts_root {
+            return Err(anyhow!("Receipts of block {} don't match its receipts root.", execution.block_number));
+        }
+
+        Ok(proofs.into_iter().map(LightClientUpdatePayload::ReceiptProof).collect())
+    }
+
     async fn check_and_sync(&mut self) -> Result<()> {
         let current_time_ns = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
         let current_time_ns = current_time_ns.as_nanos().try_into()?;
         let current_time_sec = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
         let current_time_sec = current_time_sec.as_secs();
         let genesis_time = self.genesis_time;
-        let current_slot = expected_current_slot(current_time_ns, genesis_time);
+        let current_slot = expected_current_slot::<S>(current_time_ns, genesis_time);
 
         if current_time_sec - self.last_updated_time_sec < 12 {
             return Ok(());
@@ -118,9 +201,9 @@ impl EthereumChain {
         let finalized_slot = self.light_client_store.get_finalized_slot();
         let is_next_sync_committee_known = self.light_client_store.is_next_sync_committee_known();
 
-        let current_period = calc_sync_period::<MainnetConsensusSpec>(current_slot);
-        let optimistic_period = calc_sync_period::<MainnetConsensusSpec>(optimistic_slot);
-        let finalized_period = calc_sync_period::<MainnetConsensusSpec>(finalized_slot);
+        let current_period = calc_sync_period::<S>(current_slot);
+        let optimistic_period = calc_sync_period::<S>(optimistic_slot);
+        let finalized_period = calc_sync_period::<S>(finalized_slot);
 
         if finalized_period == optimistic_period && is_next_sync_committee_known == false {
             let update = self.consensus_api.updates(finalized_period, 1).await?;
@@ -145,7 +228,7 @@ impl EthereumChain {
 
     async fn sync(&mut self, current_period: u64, mut finalized_period: u64) -> Result<()> {
         println!("Syncing...");
-        let mut updates: Vec<Update<MainnetConsensusSpec>> = vec![];
+        let mut updates: Vec<Update<S>> = vec![];
         if current_period - finalized_period >= 128 {
             while finalized_period < current_period {
                 let batch_size =
@@ -186,10 +269,17 @@ impl EthereumChain {
         Ok(())
     }
 
-    fn verify_and_apply_generic_update(&mut self, update: GenericUpdate<MainnetConsensusSpec>) -> Result<()> {
+    fn verify_and_apply_generic_update(&mut self, update: GenericUpdate<S>) -> Result<()> {
         let current_time_ns = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
         let current_time_ns = current_time_ns.as_nanos();
         let current_time_ns = current_time_ns.try_into()?;
-        self.light_client_store.update(&update, current_time_ns)
+        let reference = self.light_client_store.get_store().clone();
+        self.light_client_store.update(&update, current_time_ns)?;
+
+        if self.light_client_store.diff(&reference).is_some() {
+            self.state_differ.add_update(update);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,46 @@
+use crate::util::ExecutionApi;
+use alloy_consensus::TxEnvelope;
+use alloy_primitives::B256;
+use anyhow::{anyhow, bail, Result};
+use ic_lightclient_ethereum::{config::EthereumConfigPopulated, execution::TransactionProof};
+use ic_lightclient_oc_utils::{IcpAgent, TransactionProof as CanisterTransactionProof};
+use ic_lightclient_wire::ChainId;
+
+/// Proves the inclusion of a mined transaction against its block's transactions root and has the
+/// canister verify it. The block has to be one of the canister's verified headers.
+pub async fn prove_transaction(chain: ChainId, tx_hash: &str) -> Result<()> {
+    let config = IcpAgent::get_canister_config(chain).await?;
+    let config: EthereumConfigPopulated = serde_json::from_slice(config.as_slice())?;
+    let execution_api = ExecutionApi::new(config.execution_api);
+
+    let hash: B256 = tx_hash.parse()?;
+    let transaction = execution_api
+        .transaction_by_hash(hash)
+        .await?
+        .ok_or(anyhow!("Transaction {} not found.", tx_hash))?;
+    let (Some(block_number), Some(index)) = (transaction.block_number, transaction.transaction_index) else {
+        bail!("Transaction {} is not mined yet.", tx_hash);
+    };
+
+    let block = execution_api.block_by_number(block_number).await?;
+    let header = block.header.inner.clone();
+    let transactions: Vec<TxEnvelope> = block
+        .into_transactions_vec()
+        .into_iter()
+        .map(|transaction| transaction.inner.into_inner())
+        .collect();
+
+    let proof = TransactionProof::build(&header, &transactions, index);
+    let proof = CanisterTransactionProof {
+        block_number: proof.block_number,
+        index: proof.index,
+        header: proof.header.to_vec(),
+        transaction: proof.transaction.to_vec(),
+        proof: proof.proof.into_iter().map(|node| node.to_vec()).collect(),
+    };
+
+    let transaction = IcpAgent::verify_transaction(chain, tx_hash.to_string(), proof).await?;
+    println!("Chain {}: transaction {} verified: {}", chain, tx_hash, transaction);
+
+    Ok(())
+}
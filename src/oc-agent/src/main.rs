@@ -7,13 +7,17 @@ mod http;
 mod outcalls;
 mod util;
 
-use crate::{blueprint::build_chain_from_uid, cli::Cli, config::Config};
+use crate::{
+    blueprint::{backfill_headers_from_uid, build_chain_from_uid, prove_ancestry_from_uid},
+    cli::Cli,
+    config::Config,
+};
 use anyhow::{anyhow, Result};
 use chain::ChainManager;
-use ic_lightclient_oc_utils::IcpAgent;
-use ic_lightclient_wire::{StatePayloadParser, UpdatePayloadMarshaller};
-use std::{sync::Arc, time::Duration};
-use tokio::{sync::Mutex, task::JoinSet, time::sleep};
+use ic_lightclient_oc_utils::{ChainUpdateReport, IcpAgent};
+use ic_lightclient_wire::UpdatePayloadMarshaller;
+use std::time::Duration;
+use tokio::{task::JoinSet, time::sleep};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -24,8 +28,21 @@ async fn main() -> Result<()> {
 
     IcpAgent::init(Config::icp()).await?;
 
+    if let Some(tx_hash) = Cli::prove_transaction() {
+        return ethereum::prove_transaction(Cli::chain(), &tx_hash).await;
+    }
+
+    if let Some(slot) = Cli::prove_ancestry() {
+        return prove_ancestry_from_uid(Cli::chain(), slot).await;
+    }
+
+    if let Some(from_block) = Cli::backfill() {
+        return backfill_headers_from_uid(Cli::chain(), from_block).await;
+    }
+
     let mut chain_manager = ChainManager::new();
     let configured_chains = IcpAgent::list_chain_uids().await?;
+    let wire_versions = IcpAgent::supported_wire_versions().await?;
 
     for uid in configured_chains {
         let config = IcpAgent::get_canister_config(uid).await?;
@@ -33,37 +50,66 @@ async fn main() -> Result<()> {
         chain_manager.set(uid, chain.clone());
 
         let mut chain = chain.lock().await;
+        chain.check_wire_versions(&wire_versions)?;
         chain.init(config).await?;
     }
 
     loop {
-        let state = IcpAgent::get_canister_state().await?;
-
-        let state = StatePayloadParser::new(state)?;
-        let state = Arc::new(state);
-        let updates = Arc::new(Mutex::new(UpdatePayloadMarshaller::new()));
+        let digests = IcpAgent::get_state_digest().await?;
         let uids = chain_manager.list();
         let mut join_set = JoinSet::new();
 
         for uid in uids {
             let chain = chain_manager.get(&uid).ok_or(anyhow!("Chain not found in ChainManager"))?;
-            let state = state.clone();
-            let updates = updates.clone();
+            let digest = digests
+                .iter()
+                .find(|digest| digest.chain == uid)
+                .cloned()
+                .ok_or(anyhow!("No digest for chain {}", uid))?;
 
+            // Chains are fetched concurrently into their own marshallers, merged once all are done.
             join_set.spawn(async move {
                 let mut chain = chain.lock().await;
-                let mut updates: tokio::sync::MutexGuard<'_, UpdatePayloadMarshaller> = updates.lock().await;
-                chain.get_updates(&state, &mut updates).await.unwrap();
+                let mut updates = update_payload_marshaller();
+                if let Err(e) = chain.get_updates(&digest, &mut updates).await {
+                    println!("Chain {}: failed to get updates: {:?}", uid, e);
+                }
+
+                updates
             });
         }
 
-        join_set.join_all().await;
+        let mut updates = update_payload_marshaller();
+        for chain_updates in join_set.join_all().await {
+            updates.merge(chain_updates);
+        }
 
-        let updates = updates.lock().await;
         if updates.has_updates() {
-            IcpAgent::update_canister_state(updates.build().unwrap()).await?;
+            // Updates rejected with a conflict are recomputed on the next iteration, against the
+            // state synced from the new digests.
+            let reports = IcpAgent::update_canister_state(updates.build_upload()?).await?;
+            log_update_reports(&reports);
         }
 
         sleep(Duration::from_secs(1)).await;
     }
 }
+
+fn update_payload_marshaller() -> UpdatePayloadMarshaller {
+    let marshaller =
+        if Cli::json_payloads() { UpdatePayloadMarshaller::json() } else { UpdatePayloadMarshaller::new() };
+    marshaller.with_budget(Config::icp().max_payload_bytes)
+}
+
+fn log_update_reports(reports: &[ChainUpdateReport]) {
+    for report in reports {
+        println!(
+            "Chain {}: applied {} updates, {} pending, optimistic slot: {:?}, finalized slot: {:?}",
+            report.chain, report.applied, report.pending, report.optimistic_slot, report.finalized_slot
+        );
+
+        for rejected in &report.rejected {
+            println!("Chain {}: update {} rejected: {}", report.chain, rejected.index, rejected.error);
+        }
+    }
+}
@@ -56,6 +56,7 @@ impl OutcallsChain {
 
         let latest_block_num = self.execution_apis[0].latest_block_number().await?;
         let block_hash = self.execution_apis[0].block_header_by_number(latest_block_num).await?;
+        let timestamp = block_hash.timestamp;
         let block_hash = block_hash.hash.to_string();
         let latest_block_num = latest_block_num.try_into()?;
         let base_fee = self.execution_apis[0].base_gas_fee().await?;
@@ -63,7 +64,8 @@ impl OutcallsChain {
         let max_priority_fee = self.execution_apis[0].max_priority_fee().await?;
         let max_priority_fee = max_priority_fee.try_into()?;
 
-        let block = Block { block_num: latest_block_num, block_hash, base_gas_fee: base_fee, max_priority_fee };
+        let block =
+            Block { block_num: latest_block_num, block_hash, base_gas_fee: base_fee, max_priority_fee, timestamp };
 
         Ok(Some(block))
     }
@@ -1,27 +1,42 @@
 use crate::http::HttpClient;
 use alloy_primitives::B256;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ic_lightclient_ethereum::helios::{
-    spec::MainnetConsensusSpec,
-    types::{Bootstrap, FinalityUpdate, OptimisticUpdate, Update},
+    spec::ConsensusSpec,
+    types::{Bootstrap, FinalityUpdate, ForkName, OptimisticUpdate, Update},
 };
 use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::{marker::PhantomData, time::Duration};
+
+/// Beacon states are hundreds of megabytes on mainnet.
+const STATE_REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
 
 #[derive(Debug, Deserialize)]
 struct ResponseWrapper<T> {
-    #[allow(dead_code)]
-    version: String,
+    version: ForkName,
+    data: T,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataWrapper<T> {
     data: T,
 }
 
+#[derive(Debug, Deserialize)]
+struct BlockRoot {
+    root: B256,
+}
+
 #[derive(Default, Clone)]
-pub struct ConsensusApi {
+pub struct ConsensusApi<S: ConsensusSpec> {
     url: String,
+    _spec: PhantomData<S>,
 }
 
-impl ConsensusApi {
+impl<S: ConsensusSpec + DeserializeOwned> ConsensusApi<S> {
     pub fn new(url: String) -> Self {
-        Self { url }
+        Self { url, _spec: PhantomData }
     }
 
     async fn request<Response: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> Result<Response> {
@@ -39,37 +54,67 @@ impl ConsensusApi {
         Ok(response)
     }
 
-    pub async fn bootstrap(&self, block_root: B256) -> Result<Bootstrap<MainnetConsensusSpec>> {
-        let response: ResponseWrapper<Bootstrap<MainnetConsensusSpec>> = self
+    pub async fn bootstrap(&self, block_root: B256) -> Result<Bootstrap<S>> {
+        let response: ResponseWrapper<Value> = self
             .request(&format!("/eth/v1/beacon/light_client/bootstrap/{}", block_root), &[])
             .await?;
 
-        Ok(response.data)
+        Ok(Bootstrap::deserialize_for_fork(response.version, response.data)?)
+    }
+
+    pub async fn block_root(&self, slot: u64) -> Result<B256> {
+        let response: DataWrapper<BlockRoot> =
+            self.request(&format!("/eth/v1/beacon/blocks/{}/root", slot), &[]).await?;
+
+        Ok(response.data.root)
+    }
+
+    /// SSZ encoded beacon state at `slot`, along with the fork of its container.
+    pub async fn state(&self, slot: u64) -> Result<(ForkName, Vec<u8>)> {
+        let url = format!("{}/eth/v2/debug/beacon/states/{}", self.url, slot);
+
+        let response = HttpClient::get(&url)
+            .header("Accept", "application/octet-stream")
+            .timeout(STATE_REQUEST_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let version = response
+            .headers()
+            .get("Eth-Consensus-Version")
+            .ok_or(anyhow!("Beacon state response of slot {} has no consensus version.", slot))?
+            .to_str()?;
+        let fork = serde_json::from_value(Value::String(version.to_lowercase()))?;
+
+        Ok((fork, response.bytes().await?.to_vec()))
     }
 
-    pub async fn updates(&self, start_period: u64, count: u64) -> Result<Vec<Update<MainnetConsensusSpec>>> {
-        let response: Vec<ResponseWrapper<Update<MainnetConsensusSpec>>> = self
+    pub async fn updates(&self, start_period: u64, count: u64) -> Result<Vec<Update<S>>> {
+        let response: Vec<ResponseWrapper<Value>> = self
             .request(
                 "/eth/v1/beacon/light_client/updates",
                 &[("start_period", &start_period.to_string()), ("count", &count.to_string())],
             )
             .await?;
 
-        let response = response.into_iter().map(|r| r.data).collect();
+        let response = response
+            .into_iter()
+            .map(|r| Update::deserialize_for_fork(r.version, r.data))
+            .collect::<Result<_, _>>()?;
         Ok(response)
     }
 
-    pub async fn optimistic_update(&self) -> Result<OptimisticUpdate<MainnetConsensusSpec>> {
-        let response: ResponseWrapper<OptimisticUpdate<MainnetConsensusSpec>> =
+    pub async fn optimistic_update(&self) -> Result<OptimisticUpdate<S>> {
+        let response: ResponseWrapper<OptimisticUpdate<S>> =
             self.request("/eth/v1/beacon/light_client/optimistic_update", &[]).await?;
 
         Ok(response.data)
     }
 
-    pub async fn finality_update(&self) -> Result<FinalityUpdate<MainnetConsensusSpec>> {
-        let response: ResponseWrapper<FinalityUpdate<MainnetConsensusSpec>> =
-            self.request("/eth/v1/beacon/light_client/finality_update", &[]).await?;
+    pub async fn finality_update(&self) -> Result<FinalityUpdate<S>> {
+        let response: ResponseWrapper<Value> = self.request("/eth/v1/beacon/light_client/finality_update", &[]).await?;
 
-        Ok(response.data)
+        Ok(FinalityUpdate::deserialize_for_fork(response.version, response.data)?)
     }
 }
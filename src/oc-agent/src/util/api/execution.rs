@@ -1,8 +1,8 @@
 use std::{cell::RefCell, marker::PhantomData, rc::Rc};
 
 use crate::http::HttpClient;
-use alloy_primitives::U256;
-use alloy_rpc_types_eth::Header;
+use alloy_primitives::{Address, B256, U256};
+use alloy_rpc_types_eth::{Block, EIP1186AccountProofResponse, Header, Transaction, TransactionReceipt};
 use anyhow::{anyhow, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::value::RawValue;
@@ -90,6 +90,28 @@ impl ExecutionApi {
     pub async fn max_priority_fee(&self) -> Result<U256> {
         self.request("eth_maxPriorityFeePerGas", ()).await
     }
+
+    pub async fn block_receipts(&self, block_number: u64) -> Result<Vec<TransactionReceipt>> {
+        self.request("eth_getBlockReceipts", (U256::from(block_number),)).await
+    }
+
+    pub async fn block_by_number(&self, block_number: u64) -> Result<Block> {
+        self.request("eth_getBlockByNumber", (U256::from(block_number), true)).await
+    }
+
+    pub async fn transaction_by_hash(&self, tx_hash: B256) -> Result<Option<Transaction>> {
+        self.request("eth_getTransactionByHash", (tx_hash,)).await
+    }
+
+    pub async fn proof(
+        &self,
+        address: Address,
+        storage_slots: &[B256],
+        block_number: u64,
+    ) -> Result<EIP1186AccountProofResponse> {
+        self.request("eth_getProof", (address, storage_slots, U256::from(block_number)))
+            .await
+    }
 }
 
 struct InnerReceipt {
@@ -154,6 +176,10 @@ impl ExecutionRequestBatch {
     pub fn max_priority_fee(&mut self) -> Result<ExecutionRequestReceipt<U256>> {
         self.request("eth_maxPriorityFeePerGas", ())
     }
+
+    pub fn block_header_by_number(&mut self, block_number: u64) -> Result<ExecutionRequestReceipt<Header>> {
+        self.request("eth_getBlockByNumber", (U256::from(block_number), false))
+    }
 }
 
 pub struct ExecutionRequestReceipt<T: DeserializeOwned> {
@@ -2,4 +2,4 @@ mod consensus;
 mod execution;
 
 pub use consensus::ConsensusApi;
-pub use execution::ExecutionApi;
+pub use execution::{ExecutionApi, ExecutionRequestBatch};
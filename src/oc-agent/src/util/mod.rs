@@ -1,5 +1,5 @@
 mod api;
 mod blockstore;
 
-pub use api::{ConsensusApi, ExecutionApi};
+pub use api::{ConsensusApi, ExecutionApi, ExecutionRequestBatch};
 pub use blockstore::EthereumBlockStore;
@@ -1,11 +1,17 @@
 use anyhow::{anyhow, Context, Result};
-use candid::Principal;
-use ic_agent::Agent;
+use candid::{CandidType, IDLValue, Principal};
+use ic_agent::{identity::Secp256k1Identity, Agent};
+use ic_lightclient_wire::{ChainId, ChainStateDigest, ChunkedUpload, SupportedWireVersions, UpdateUpload};
 use ic_utils::{call::SyncCall, Canister};
 use serde::{Deserialize, Serialize};
-use std::sync::OnceLock;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    OnceLock,
+};
 
 static INNER: OnceLock<Inner> = OnceLock::new();
+/// Id of the next upload session, so concurrent uploads of this agent don't replace each other.
+static NEXT_UPLOAD_SESSION: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Debug)]
 struct Inner {
@@ -17,16 +23,72 @@ struct Inner {
 pub struct IcpConfig {
     pub canister_id: Principal,
     pub agent_url: String,
+    /// Secp256k1 PEM file of the identity to call the canister with, anonymous if not set.
+    #[serde(default)]
+    pub identity_pem: Option<String>,
+    /// Update payloads larger than this are sent in chunks over an upload session.
+    #[serde(default = "default_max_payload_bytes")]
+    pub max_payload_bytes: usize,
+}
+
+/// Leaves room below the 2 MiB ingress limit for the candid encoding of the call.
+fn default_max_payload_bytes() -> usize {
+    1_900_000
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct RejectedUpdate {
+    pub index: u64,
+    pub error: IDLValue,
+}
+
+/// Outcome of the updates submitted for one chain, as reported by the canister.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct ChainUpdateReport {
+    pub chain: ChainId,
+    pub applied: u64,
+    pub pending: u64,
+    pub rejected: Vec<RejectedUpdate>,
+    pub optimistic_slot: Option<u64>,
+    pub finalized_slot: Option<u64>,
+}
+
+/// Transaction-trie proof of a transaction, built by the agent and checked by the canister.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct TransactionProof {
+    pub block_number: u64,
+    pub index: u64,
+    pub header: Vec<u8>,
+    pub transaction: Vec<u8>,
+    pub proof: Vec<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Debug, Clone, Copy)]
+pub enum Commitment {
+    Optimistic,
+    Finalized,
+}
+
+/// Fields of the canister's verified headers the agent relies on.
+#[derive(CandidType, Deserialize, Debug, Clone)]
+pub struct BlockHeader {
+    pub commitment: Commitment,
+    pub beacon_slot: u64,
+    pub execution_block_number: u64,
+    pub execution_block_hash: String,
 }
 
 pub struct IcpAgent;
 
 impl IcpAgent {
     pub async fn init(config: IcpConfig) -> Result<()> {
-        let agent = Agent::builder()
-            .with_url(config.agent_url.clone())
-            .build()
-            .context("Failed to create agent")?;
+        let mut builder = Agent::builder().with_url(config.agent_url.clone());
+        if let Some(identity_pem) = &config.identity_pem {
+            let identity = Secp256k1Identity::from_pem_file(identity_pem).context("Failed to load identity")?;
+            builder = builder.with_identity(identity);
+        }
+
+        let agent = builder.build().context("Failed to create agent")?;
 
         agent.fetch_root_key().await?;
         INNER
@@ -48,31 +110,68 @@ impl IcpAgent {
 
     pub async fn get_canister_state() -> Result<Vec<u8>> {
         let canister = IcpAgent::canister()?;
-        let (state,) = canister
+        let (result,): (std::result::Result<Vec<u8>, IDLValue>,) = canister
             .query("get_state")
             .build()
             .call()
             .await
             .context("Failed to get canister state")?;
 
-        Ok(state)
+        result.map_err(|e| anyhow!("Canister rejected state query: {}", e))
     }
 
-    pub async fn list_chain_uids() -> Result<Vec<u16>> {
+    pub async fn get_state_digest() -> Result<Vec<ChainStateDigest>> {
         let canister = IcpAgent::canister()?;
-        let (uids,) = canister
+        let (result,): (std::result::Result<Vec<ChainStateDigest>, IDLValue>,) = canister
+            .query("get_state_digest")
+            .build()
+            .call()
+            .await
+            .context("Failed to get state digest")?;
+
+        result.map_err(|e| anyhow!("Canister rejected state digest query: {}", e))
+    }
+
+    pub async fn supported_wire_versions() -> Result<SupportedWireVersions> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<SupportedWireVersions, IDLValue>,) = canister
+            .query("supported_wire_versions")
+            .build()
+            .call()
+            .await
+            .context("Failed to get supported wire versions")?;
+
+        result.map_err(|e| anyhow!("Canister rejected wire versions query: {}", e))
+    }
+
+    pub async fn get_chain_state(uid: ChainId, since_version: Option<u64>) -> Result<Vec<u8>> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<Vec<u8>, IDLValue>,) = canister
+            .query("get_chain_state")
+            .with_args((uid, since_version))
+            .build()
+            .call()
+            .await
+            .context("Failed to get chain state")?;
+
+        result.map_err(|e| anyhow!("Canister rejected chain state query: {}", e))
+    }
+
+    pub async fn list_chain_uids() -> Result<Vec<ChainId>> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<Vec<ChainId>, IDLValue>,) = canister
             .query("list_chain_uids")
             .build()
             .call()
             .await
             .context("Failed to get configured chain uids")?;
 
-        Ok(uids)
+        result.map_err(|e| anyhow!("Canister rejected chain uids query: {}", e))
     }
 
-    pub async fn get_canister_config(uid: u16) -> Result<Vec<u8>> {
+    pub async fn get_canister_config(uid: ChainId) -> Result<Vec<u8>> {
         let canister = IcpAgent::canister()?;
-        let (state,) = canister
+        let (result,): (std::result::Result<Vec<u8>, IDLValue>,) = canister
             .query("get_chain_config")
             .with_arg(uid)
             .build()
@@ -80,28 +179,118 @@ impl IcpAgent {
             .await
             .context("Failed to get canister config")?;
 
-        Ok(state)
+        result.map_err(|e| anyhow!("Canister rejected config query: {}", e))
+    }
+
+    pub async fn update_canister_state(upload: UpdateUpload) -> Result<Vec<ChainUpdateReport>> {
+        match upload {
+            UpdateUpload::Single(updates) => IcpAgent::update_state(updates).await,
+            UpdateUpload::Chunked(upload) => IcpAgent::upload_state(upload).await,
+        }
     }
 
-    pub async fn update_canister_state(updates: Vec<u8>) -> Result<()> {
+    async fn update_state(updates: Vec<u8>) -> Result<Vec<ChainUpdateReport>> {
         let canister = IcpAgent::canister()?;
-        canister
+        let (result,): (std::result::Result<Vec<ChainUpdateReport>, IDLValue>,) = canister
             .update("update_state")
             .with_arg(updates)
             .build()
             .call_and_wait()
             .await
-            .context("Failed to update canister state")
+            .context("Failed to update canister state")?;
+
+        result.map_err(|e| anyhow!("Canister rejected state update: {}", e))
     }
 
-    pub async fn set_config(name: u16, value: String) -> Result<()> {
+    /// Every upload gets its own session. One which fails halfway is committed anyway to close
+    /// it, so it doesn't hold on to the caller's sessions until it times out.
+    async fn upload_state(upload: ChunkedUpload) -> Result<Vec<ChainUpdateReport>> {
         let canister = IcpAgent::canister()?;
-        canister
+        let session = NEXT_UPLOAD_SESSION.fetch_add(1, Ordering::Relaxed);
+
+        let (result,): (std::result::Result<(), IDLValue>,) = canister
+            .update("begin_upload")
+            .with_args((session, upload.total_len, upload.sha256))
+            .build()
+            .call_and_wait()
+            .await
+            .context("Failed to begin upload")?;
+        result.map_err(|e| anyhow!("Canister rejected upload: {}", e))?;
+
+        let appended = IcpAgent::append_chunks(&canister, session, upload.chunks).await;
+
+        let committed: std::result::Result<(std::result::Result<Vec<ChainUpdateReport>, IDLValue>,), _> =
+            canister.update("commit_upload").with_arg(session).build().call_and_wait().await;
+
+        appended?;
+        let (result,) = committed.context("Failed to commit upload")?;
+        result.map_err(|e| anyhow!("Canister rejected state update: {}", e))
+    }
+
+    async fn append_chunks(canister: &Canister<'_>, session: u64, chunks: Vec<Vec<u8>>) -> Result<()> {
+        for chunk in chunks {
+            let (result,): (std::result::Result<u64, IDLValue>,) = canister
+                .update("append_upload")
+                .with_args((session, chunk))
+                .build()
+                .call_and_wait()
+                .await
+                .context("Failed to append upload chunk")?;
+            result.map_err(|e| anyhow!("Canister rejected upload chunk: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_config(chain: ChainId, value: String) -> Result<()> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<(), IDLValue>,) = canister
             .update("set_config")
-            .with_args((name, value))
+            .with_args((chain, value))
             .build()
             .call_and_wait()
             .await
-            .context("Failed to set config in canister")
+            .context("Failed to set config in canister")?;
+
+        result.map_err(|e| anyhow!("Canister rejected config: {}", e))
+    }
+
+    pub async fn get_latest_block(chain: ChainId, commitment: Commitment) -> Result<BlockHeader> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<BlockHeader, IDLValue>,) = canister
+            .query("get_latest_block")
+            .with_args((chain, commitment, None::<u64>))
+            .build()
+            .call()
+            .await
+            .context("Failed to get latest block")?;
+
+        result.map_err(|e| anyhow!("Canister rejected latest block query: {}", e))
+    }
+
+    pub async fn verify_ancestry(chain: ChainId, proof: Vec<u8>) -> Result<IDLValue> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<IDLValue, IDLValue>,) = canister
+            .query("verify_ancestry")
+            .with_args((chain, proof))
+            .build()
+            .call()
+            .await
+            .context("Failed to verify ancestry")?;
+
+        result.map_err(|e| anyhow!("Canister rejected ancestry proof: {}", e))
+    }
+
+    pub async fn verify_transaction(chain: ChainId, tx_hash: String, proof: TransactionProof) -> Result<IDLValue> {
+        let canister = IcpAgent::canister()?;
+        let (result,): (std::result::Result<IDLValue, IDLValue>,) = canister
+            .query("verify_transaction")
+            .with_args((chain, tx_hash, proof))
+            .build()
+            .call()
+            .await
+            .context("Failed to verify transaction")?;
+
+        result.map_err(|e| anyhow!("Canister rejected transaction proof: {}", e))
     }
 }
@@ -1,3 +1,3 @@
 mod icp;
 
-pub use icp::{IcpAgent, IcpConfig};
+pub use icp::{BlockHeader, ChainUpdateReport, Commitment, IcpAgent, IcpConfig, RejectedUpdate, TransactionProof};
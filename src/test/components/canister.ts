@@ -1,10 +1,44 @@
 import { tmpdir } from "os";
 import { spawnAndWait } from "../process";
 import { realpathSync } from "fs";
-import { readFile } from "fs/promises";
+import { readFile, writeFile } from "fs/promises";
 import { join } from "path";
 
+const TEST_IDENTITY: string = 'oraclekit-test';
+
 let canisterId: string;
+let configFile: string;
+
+// Creates the identity the tool and the oc-agent call the canister with, and a copy of the
+// config which points them at its PEM file. Returns the principal of the identity.
+async function setupTestIdentity(tempDirPath: string): Promise<string> {
+    const pemFile = join(tempDirPath, 'ORACLEKIT_TMP_identity.pem');
+    const principalFile = join(tempDirPath, 'ORACLEKIT_TMP_principal');
+    configFile = join(tempDirPath, 'ORACLEKIT_TMP_oraclekit.toml');
+
+    let ret = await spawnAndWait('dfx', ['identity', 'new', TEST_IDENTITY, '--storage-mode', 'plaintext', '--force']);
+    if ( ret !== 0 ) throw "Unable to create test identity.";
+
+    ret = await spawnAndWait('bash', ['-c', `dfx identity export ${TEST_IDENTITY} > ${pemFile}`]);
+    if ( ret !== 0 ) throw "Unable to export test identity.";
+
+    ret = await spawnAndWait('bash', ['-c', `dfx identity get-principal --identity ${TEST_IDENTITY} > ${principalFile}`]);
+    if ( ret !== 0 ) throw "Unable to get test identity principal.";
+
+    const principal = (await readFile(principalFile, { encoding: 'utf-8' })).trim();
+
+    ret = await spawnAndWait('rm', [principalFile]);
+    if ( ret !== 0 ) throw "Unable to cleanup.";
+
+    const config = await readFile('oraclekit.toml', { encoding: 'utf-8' });
+    await writeFile(configFile, config.replace('[icp]', `[icp]\nidentity_pem="${pemFile}"`));
+
+    return principal;
+}
+
+export function getConfigFile(): string {
+    return configFile;
+}
 
 export async function setupCanister(): Promise<string> {
     let ret = await spawnAndWait('dfx', ['deploy', 'canister']);
@@ -22,11 +56,21 @@ export async function setupCanister(): Promise<string> {
     ret = await spawnAndWait('rm', [canisterFile]);
     if ( ret !== 0 ) throw "Unable to cleanup.";
 
-    ret = await spawnAndWait('bash', ['-c', './target/debug/ic-lightclient-tool']);
+    const principal = await setupTestIdentity(resolvedTempDirPath);
+
+    ret = await spawnAndWait('dfx', ['canister', 'call', "canister", "add_admin", `(principal "${principal}")`]);
+    if ( ret !== 0 ) throw "Unable to add config admin.";
+
+    ret = await spawnAndWait('bash', ['-c', `./target/debug/ic-lightclient-tool ${configFile}`]);
     if ( ret !== 0 ) throw "Unable to init config.";
 
-    ret = await spawnAndWait('dfx', ['canister', 'call', "canister", "init", "(vec {1 : nat16; 17000: nat16})"]);
+    ret = await spawnAndWait('dfx', ['canister', 'call', "canister", "init", "(vec {1 : nat64; 17000: nat64})"]);
     if ( ret !== 0 ) throw "Unable to init canister";
 
+    for ( const chain of [1, 17000] ) {
+        ret = await spawnAndWait('dfx', ['canister', 'call', "canister", "add_agent", `(${chain} : nat64, principal "${principal}")`]);
+        if ( ret !== 0 ) throw "Unable to add agent.";
+    }
+
     return canisterId;
 }
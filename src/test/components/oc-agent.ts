@@ -2,8 +2,8 @@ import { isChildHealthy, spawn } from "../process";
 
 let processId: number | undefined;
 
-export async function setupOcAgent() {
-    processId = await spawn('bash', ['-c', './target/debug/ic-lightclient-oc-agent']);
+export async function setupOcAgent(configFile: string) {
+    processId = await spawn('bash', ['-c', `./target/debug/ic-lightclient-oc-agent --config-file ${configFile}`]);
 }
 
 export async function checkOcAgentHealthy(): Promise<boolean> {
@@ -1,15 +1,20 @@
 import {describe, expect, test} from '@jest/globals';
 import { spawnAndWait, terminate } from './process';
 import { setupDfx } from './components/dfx';
-import { setupCanister } from './components/canister';
+import { getConfigFile, setupCanister } from './components/canister';
 import { checkOcAgentHealthy, setupOcAgent } from './components/oc-agent';
 import { createActor } from './declarations/canister';
 import { HttpAgent } from '@dfinity/agent';
 
 let agent: HttpAgent;
 
-const EthereumMainnetChainId = 1;
-const EthereumHoleskyChainId = 17000;
+const EthereumMainnetChainId = 1n;
+const EthereumHoleskyChainId = 17000n;
+
+// Queries fail with e.g. NotBootstrapped until the agent has pushed the first updates.
+function ok<T>(result: { Ok: T } | { Err: unknown }): T | undefined {
+    return 'Ok' in result ? result.Ok : undefined;
+}
 
 describe('test e2e', () => {
     beforeAll(async () => {
@@ -23,32 +28,32 @@ describe('test e2e', () => {
         let canisterId = await setupCanister();
         let actor = createActor(canisterId, { agent });
         let mainnet = {
-            block_hash: await actor.get_latest_block_hash(EthereumMainnetChainId),
-            base_gas_fee: await actor.get_base_gas_fee(EthereumMainnetChainId),
-            max_priority_fee: await actor.get_max_priority_fee(EthereumMainnetChainId)
+            block_hash: ok(await actor.get_latest_block_hash(EthereumMainnetChainId, [])),
+            base_gas_fee: ok(await actor.get_base_gas_fee(EthereumMainnetChainId, [])),
+            max_priority_fee: ok(await actor.get_max_priority_fee(EthereumMainnetChainId, []))
         };
 
         let holesky = {
-            block_hash: await actor.get_latest_block_hash(EthereumHoleskyChainId),
-            base_gas_fee: await actor.get_base_gas_fee(EthereumHoleskyChainId),
-            max_priority_fee: await actor.get_max_priority_fee(EthereumHoleskyChainId)
+            block_hash: ok(await actor.get_latest_block_hash(EthereumHoleskyChainId, [])),
+            base_gas_fee: ok(await actor.get_base_gas_fee(EthereumHoleskyChainId, [])),
+            max_priority_fee: ok(await actor.get_max_priority_fee(EthereumHoleskyChainId, []))
         };
 
-        await setupOcAgent();
+        await setupOcAgent(getConfigFile());
         await new Promise(resolve => setTimeout(resolve, 30*1000));
 
         expect(await checkOcAgentHealthy()).toBe(true);
         
         let mainnet_new = {
-            block_hash: await actor.get_latest_block_hash(EthereumMainnetChainId),
-            base_gas_fee: await actor.get_base_gas_fee(EthereumMainnetChainId),
-            max_priority_fee: await actor.get_max_priority_fee(EthereumMainnetChainId)
+            block_hash: ok(await actor.get_latest_block_hash(EthereumMainnetChainId, [])),
+            base_gas_fee: ok(await actor.get_base_gas_fee(EthereumMainnetChainId, [])),
+            max_priority_fee: ok(await actor.get_max_priority_fee(EthereumMainnetChainId, []))
         };
 
         let holesky_new = {
-            block_hash: await actor.get_latest_block_hash(EthereumHoleskyChainId),
-            base_gas_fee: await actor.get_base_gas_fee(EthereumHoleskyChainId),
-            max_priority_fee: await actor.get_max_priority_fee(EthereumHoleskyChainId)
+            block_hash: ok(await actor.get_latest_block_hash(EthereumHoleskyChainId, [])),
+            base_gas_fee: ok(await actor.get_base_gas_fee(EthereumHoleskyChainId, [])),
+            max_priority_fee: ok(await actor.get_max_priority_fee(EthereumHoleskyChainId, []))
         };
 
         expect(typeof mainnet.block_hash).toBe('string');
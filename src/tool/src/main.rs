@@ -1,30 +1,38 @@
 use ic_lightclient_ethereum::config::EthereumConfig;
 use ic_lightclient_oc_utils::{IcpAgent, IcpConfig};
-use ic_lightclient_wire::ethereum::outcalls;
+use ic_lightclient_wire::{ethereum::outcalls, ChainId};
 use serde::{Deserialize, Serialize};
-use std::fs::read_to_string;
+use std::{env, fs::read_to_string};
 
 #[derive(Deserialize, Serialize, Debug)]
 struct Config {
     icp: IcpConfig,
     ethereum: EthereumConfig,
     ethereum_holesky: outcalls::Config,
+    #[serde(default)]
+    ethereum_devnet: Option<EthereumConfig>,
 }
 
 #[tokio::main]
 async fn main() {
-    let config_file = "oraclekit.toml";
+    let config_file = env::args().nth(1).unwrap_or("oraclekit.toml".into());
     let config = read_to_string(config_file).unwrap();
     let config: Config = toml::from_str(&config).unwrap();
 
     IcpAgent::init(config.icp).await.unwrap();
-    IcpAgent::set_config(1, serde_json::to_string(&config.ethereum).unwrap())
+    IcpAgent::set_config(ChainId::ETHEREUM_MAINNET, serde_json::to_string(&config.ethereum).unwrap())
         .await
         .unwrap();
 
-    IcpAgent::set_config(17000, serde_json::to_string(&config.ethereum_holesky).unwrap())
+    IcpAgent::set_config(ChainId::ETHEREUM_HOLESKY, serde_json::to_string(&config.ethereum_holesky).unwrap())
         .await
         .unwrap();
 
+    if let Some(ethereum_devnet) = &config.ethereum_devnet {
+        IcpAgent::set_config(ChainId::ETHEREUM_DEVNET, serde_json::to_string(ethereum_devnet).unwrap())
+            .await
+            .unwrap();
+    }
+
     println!("Set config successfully.");
 }
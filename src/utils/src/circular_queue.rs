@@ -58,12 +58,23 @@ impl<T> CircularQueue<T> {
         Some(&self.buf[real_i])
     }
 
+    pub fn at_index_mut(&mut self, i: usize) -> Option<&mut T> {
+        let size = self.size();
+        if i >= size {
+            return None;
+        }
+
+        let real_i = (self.next + i) % size;
+        Some(&mut self.buf[real_i])
+    }
+
     pub fn clear(&mut self) {
         self.buf.clear();
+        self.next = 0;
     }
 
     pub fn iter(&self) -> CircularQueueIter<'_, T> {
-        CircularQueueIter { store: &self, i: 0 }
+        CircularQueueIter { store: self, i: 0 }
     }
 }
 
@@ -120,5 +131,14 @@ mod tests {
         assert_eq!(queue.size(), 0);
         assert_eq!(queue.head(), None);
         assert_eq!(queue.tail(), None);
+
+        queue.queue(500);
+        queue.queue(600);
+        queue.queue(700);
+        queue.clear();
+        queue.queue(800);
+        assert_eq!(queue.size(), 1);
+        assert_eq!(queue.head(), Some(&800));
+        assert_eq!(queue.tail(), Some(&800));
     }
 }
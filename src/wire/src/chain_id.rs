@@ -0,0 +1,33 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// EVM chain id of a supported chain, also used as its uid across the canister and agents.
+#[derive(Serialize, Deserialize, CandidType, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct ChainId(pub u64);
+
+impl ChainId {
+    pub const ETHEREUM_MAINNET: ChainId = ChainId(1);
+    pub const ETHEREUM_HOLESKY: ChainId = ChainId(17000);
+    /// Default chain id of local kurtosis devnets, which run the minimal preset.
+    pub const ETHEREUM_DEVNET: ChainId = ChainId(3151908);
+}
+
+impl From<u64> for ChainId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<u16> for ChainId {
+    fn from(id: u16) -> Self {
+        Self(id as u64)
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
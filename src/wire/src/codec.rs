@@ -0,0 +1,113 @@
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Encoding of a payload, recorded next to it so parsers don't have to guess.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CodecTag {
+    #[default]
+    Json,
+    Bincode,
+}
+
+/// Encodes the chain payloads of a `WireProtocol`.
+pub trait WireCodec<T> {
+    const TAG: CodecTag;
+
+    fn encode(value: &T) -> Result<Vec<u8>>;
+    fn decode(data: &[u8]) -> Result<T>;
+}
+
+/// Human readable encoding, kept for debugging.
+pub struct JsonCodec;
+
+impl<T: Serialize + DeserializeOwned> WireCodec<T> for JsonCodec {
+    const TAG: CodecTag = CodecTag::Json;
+
+    fn encode(value: &T) -> Result<Vec<u8>> {
+        serde_json::to_vec(value).context("Failed to encode JSON payload")
+    }
+
+    fn decode(data: &[u8]) -> Result<T> {
+        serde_json::from_slice(data).context("Failed to decode JSON payload")
+    }
+}
+
+/// Compact encoding of any serde payload. Types with a beacon API JSON representation switch to
+/// raw bytes or SSZ for it, as bincode is not human readable.
+pub struct BincodeCodec;
+
+impl<T: Serialize + DeserializeOwned> WireCodec<T> for BincodeCodec {
+    const TAG: CodecTag = CodecTag::Bincode;
+
+    fn encode(value: &T) -> Result<Vec<u8>> {
+        bincode::serde::encode_to_vec(value, bincode::config::standard()).context("Failed to encode bincode payload")
+    }
+
+    fn decode(data: &[u8]) -> Result<T> {
+        let (value, read) = bincode::serde::decode_from_slice(data, bincode::config::standard())
+            .context("Failed to decode bincode payload")?;
+        if read != data.len() {
+            bail!("Trailing bytes after bincode payload");
+        }
+
+        Ok(value)
+    }
+}
+
+/// Encodes with the protocol's codec `C`, or as JSON when `json` is set.
+pub(crate) fn encode_payload<T, C>(value: &T, json: bool) -> Result<(CodecTag, Vec<u8>)>
+where
+    T: Serialize + DeserializeOwned,
+    C: WireCodec<T>,
+{
+    if json {
+        return Ok((CodecTag::Json, JsonCodec::encode(value)?));
+    }
+
+    Ok((C::TAG, C::encode(value)?))
+}
+
+/// Decodes a payload encoded with `tag`, which needn't be the protocol's codec `C`.
+pub(crate) fn decode_payload<T, C>(tag: CodecTag, data: &[u8]) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    C: WireCodec<T>,
+{
+    match tag {
+        tag if tag == C::TAG => C::decode(data),
+        CodecTag::Json => JsonCodec::decode(data),
+        CodecTag::Bincode => BincodeCodec::decode(data),
+    }
+}
+
+/// Envelopes are either JSON objects or bincode, which starts with the varint encoded version.
+pub(crate) fn is_json_envelope(data: &[u8]) -> bool {
+    data.first() == Some(&b'{')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_payload, encode_payload, is_json_envelope, BincodeCodec, CodecTag, JsonCodec};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Payload {
+        number: u64,
+        hash: String,
+    }
+
+    #[test]
+    fn test_codecs() {
+        let payload = Payload { number: 42, hash: "0x01".into() };
+
+        let (tag, data) = encode_payload::<_, BincodeCodec>(&payload, false).unwrap();
+        assert_eq!(tag, CodecTag::Bincode);
+        assert_eq!(decode_payload::<Payload, BincodeCodec>(tag, &data).unwrap(), payload);
+
+        let (tag, data) = encode_payload::<_, BincodeCodec>(&payload, true).unwrap();
+        assert_eq!(tag, CodecTag::Json);
+        assert_eq!(decode_payload::<Payload, BincodeCodec>(tag, &data).unwrap(), payload);
+        assert!(decode_payload::<Payload, JsonCodec>(CodecTag::Bincode, &data).is_err());
+        assert!(is_json_envelope(&data));
+    }
+}
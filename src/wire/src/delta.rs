@@ -0,0 +1,93 @@
+use crate::{
+    codec::{decode_payload, encode_payload},
+    versions::decode_state,
+    BincodeCodec, ChainId, CodecTag, WireCodec, WireProtocol,
+};
+use anyhow::{bail, Context, Result};
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Version counter and hash of a chain's state, for agents to tell whether their copy is current.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChainStateDigest {
+    pub chain: ChainId,
+    pub version: u64,
+    pub hash: Vec<u8>,
+}
+
+/// Hash of the state as encoded by the protocol's codec.
+pub fn state_hash<W: WireProtocol>(state: &W::StatePayload) -> Result<Vec<u8>> {
+    let encoded = <W::Codec as WireCodec<W::StatePayload>>::encode(state)?;
+    Ok(Sha256::digest(encoded).to_vec())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateChange {
+    Unchanged,
+    Full,
+    Diff,
+}
+
+/// Chain state served to an agent holding the state at an older version: nothing, the fields
+/// changed since that version, or the full state when the diff can't be served.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ChainStateDelta {
+    pub version: u64,
+    pub hash: Vec<u8>,
+    pub change: StateChange,
+    /// `WireProtocol::PAYLOAD_VERSION` of the canister that wrote the payload.
+    pub payload_version: u64,
+    pub codec: CodecTag,
+    pub payload: Vec<u8>,
+}
+
+impl ChainStateDelta {
+    pub fn unchanged<W: WireProtocol>(version: u64, hash: Vec<u8>) -> Self {
+        Self {
+            version,
+            hash,
+            change: StateChange::Unchanged,
+            payload_version: W::PAYLOAD_VERSION,
+            codec: CodecTag::default(),
+            payload: vec![],
+        }
+    }
+
+    pub fn full<W: WireProtocol>(version: u64, hash: Vec<u8>, state: &W::StatePayload) -> Result<Self> {
+        let (codec, payload) = encode_payload::<_, W::Codec>(state, false).context("Failed to marshal chain state")?;
+        Ok(Self { version, hash, change: StateChange::Full, payload_version: W::PAYLOAD_VERSION, codec, payload })
+    }
+
+    pub fn diff<W: WireProtocol>(version: u64, hash: Vec<u8>, diff: &W::StateDiff) -> Result<Self> {
+        let (codec, payload) = encode_payload::<_, W::Codec>(diff, false).context("Failed to marshal state diff")?;
+        Ok(Self { version, hash, change: StateChange::Diff, payload_version: W::PAYLOAD_VERSION, codec, payload })
+    }
+
+    pub fn build(&self) -> Result<Vec<u8>> {
+        BincodeCodec::encode(self).context("Failed to marshal chain state delta")
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        BincodeCodec::decode(data).context("Failed to parse chain state delta")
+    }
+
+    /// Applies the delta to the state held by the agent, which may only be missing when the full
+    /// state was sent. Diffs aren't migrated, they must match the agent's payload version.
+    pub fn apply<W: WireProtocol>(&self, state: Option<W::StatePayload>) -> Result<W::StatePayload> {
+        match (self.change, state) {
+            (StateChange::Full, _) => decode_state::<W>(self.payload_version, self.codec, &self.payload),
+            (StateChange::Unchanged, Some(state)) => Ok(state),
+            (StateChange::Diff, Some(mut state)) => {
+                if self.payload_version != W::PAYLOAD_VERSION {
+                    bail!("Received a state diff of payload version {}", self.payload_version);
+                }
+
+                let diff = decode_payload::<W::StateDiff, W::Codec>(self.codec, &self.payload)?;
+                W::patch_state(&mut state, diff)?;
+                Ok(state)
+            }
+            (change, None) => bail!("Received a {:?} chain state delta without a cached state", change),
+        }
+    }
+}
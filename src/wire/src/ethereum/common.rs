@@ -6,4 +6,7 @@ pub struct Block {
     pub block_hash: String,
     pub base_gas_fee: u128,
     pub max_priority_fee: u128,
+    /// Chain reported block timestamp in seconds.
+    #[serde(default)]
+    pub timestamp: u64,
 }
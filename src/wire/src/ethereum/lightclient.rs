@@ -1,8 +1,13 @@
-use crate::protocol::WireProtocol;
+use crate::{protocol::WireProtocol, BincodeCodec};
+use anyhow::{bail, Result};
 use ic_lightclient_ethereum::{
     config::EthereumConfigPopulated,
-    helios::{spec::ConsensusSpec, types::Bootstrap},
-    payload::{LightClientState, LightClientStoreDiff},
+    execution::{AccountProof, HeaderChain, ReceiptProof},
+    helios::{
+        spec::ConsensusSpec,
+        types::{Bootstrap, FinalityUpdate, GenericUpdate, OptimisticUpdate, Update},
+    },
+    payload::{patch_store, LightClientState, LightClientStoreDiff},
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::marker::PhantomData;
@@ -12,8 +17,14 @@ pub use crate::ethereum::common::Block;
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum LightClientUpdatePayload<S: ConsensusSpec> {
     Bootstrap(Bootstrap<S>),
-    Update(LightClientStoreDiff<S>),
+    Update(Update<S>),
+    FinalityUpdate(FinalityUpdate<S>),
+    OptimisticUpdate(OptimisticUpdate<S>),
+    GenericUpdate(GenericUpdate<S>),
     Block(Block),
+    AccountProof(AccountProof),
+    ReceiptProof(ReceiptProof),
+    HeaderChain(HeaderChain),
 }
 
 pub type LightClientStatePayload<S> = LightClientState<S>;
@@ -26,4 +37,94 @@ impl<S: ConsensusSpec + Serialize + DeserializeOwned> WireProtocol for EthereumW
     type StatePayload = LightClientStatePayload<S>;
     type UpdatePayload = LightClientUpdatePayload<S>;
     type Config = EthereumConfigPopulated;
+    type StateDiff = LightClientStoreDiff<S>;
+    type Codec = BincodeCodec;
+
+    fn patch_state(state: &mut Self::StatePayload, diff: Self::StateDiff) -> Result<()> {
+        let LightClientState::Active(store) = state else {
+            bail!("Store diffs only apply to bootstrapped light clients");
+        };
+
+        patch_store(store, diff);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{state_hash, ChainId, UpdatePayloadMarshaller, UpdatePayloadParser, WireCodec};
+    use ic_lightclient_ethereum::{
+        helios::{
+            spec::MainnetConsensusSpec,
+            types::{LightClientHeader, LightClientStore, SyncAggregate, SyncCommittee, UpdateElectra},
+        },
+        payload::diff_store,
+    };
+
+    type Protocol = EthereumWireProtocol<MainnetConsensusSpec>;
+
+    #[test]
+    fn test_bincode_update_payload() {
+        let mut header = LightClientHeader::default();
+        header.beacon.slot = 11_010_048;
+        header.execution.block_number = 22_000_000;
+
+        let update = Update::Electra(UpdateElectra {
+            attested_header: header.clone(),
+            next_sync_committee: SyncCommittee::default(),
+            next_sync_committee_branch: Default::default(),
+            finalized_header: header.clone(),
+            finality_branch: Default::default(),
+            sync_aggregate: SyncAggregate::default(),
+            signature_slot: 11_010_049,
+        });
+        let generic = GenericUpdate {
+            attested_header: header.clone(),
+            signature_slot: 11_010_050,
+            finalized_header: Some(header),
+            ..Default::default()
+        };
+        let block =
+            Block { block_num: 22_000_000, block_hash: "0x01".into(), timestamp: 1_741_000_000, ..Default::default() };
+        let updates = vec![
+            LightClientUpdatePayload::Update(update),
+            LightClientUpdatePayload::GenericUpdate(generic),
+            LightClientUpdatePayload::Block(block),
+        ];
+
+        let mut marshaller = UpdatePayloadMarshaller::new();
+        marshaller
+            .updates::<Protocol>(ChainId::ETHEREUM_MAINNET, updates.clone())
+            .unwrap();
+        let parser = UpdatePayloadParser::new(marshaller.build().unwrap()).unwrap();
+        let parsed = parser
+            .updates::<Protocol>(ChainId::ETHEREUM_MAINNET)
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert!(matches!(&parsed[0], LightClientUpdatePayload::Update(Update::Electra(_))));
+        assert_eq!(BincodeCodec::encode(&parsed).unwrap(), BincodeCodec::encode(&updates).unwrap());
+    }
+
+    #[test]
+    fn test_store_diff_round_trip() {
+        let store = LightClientStore::<MainnetConsensusSpec>::default();
+        let mut reference = store.clone();
+        reference.optimistic_header.beacon.slot = 11_010_048;
+        reference.previous_max_active_participants = 480;
+        reference.current_max_active_participants = 500;
+
+        let diff = diff_store(&reference, &store).unwrap();
+        let mut state = LightClientState::Active(store);
+        Protocol::patch_state(&mut state, diff).unwrap();
+        let reference = LightClientState::Active(reference);
+        assert_eq!(state_hash::<Protocol>(&state).unwrap(), state_hash::<Protocol>(&reference).unwrap());
+
+        let LightClientState::Active(mut store) = state else { unreachable!() };
+        let LightClientState::Active(reference) = &reference else { unreachable!() };
+        store.current_max_active_participants = 0;
+        assert!(diff_store(reference, &store).is_some());
+    }
 }
@@ -1,4 +1,5 @@
-use crate::WireProtocol;
+use crate::{BincodeCodec, WireProtocol};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
 pub use crate::ethereum::common::Block;
@@ -6,6 +7,16 @@ pub use crate::ethereum::common::Block;
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Config {
     pub execution_apis: Vec<String>,
+    /// Number of agents which must report an identical block before the canister accepts it.
+    #[serde(default = "default_quorum_threshold")]
+    pub quorum_threshold: u32,
+    /// Maximum age in seconds of the served block before the chain is reported as stale.
+    #[serde(default)]
+    pub freshness_sla: Option<u64>,
+}
+
+fn default_quorum_threshold() -> u32 {
+    1
 }
 
 pub struct OutcallsWireProtocol;
@@ -14,4 +25,12 @@ impl WireProtocol for OutcallsWireProtocol {
     type StatePayload = Block;
     type UpdatePayload = Block;
     type Config = Config;
+    /// The served block is small enough to always be sent in full.
+    type StateDiff = Block;
+    type Codec = BincodeCodec;
+
+    fn patch_state(state: &mut Self::StatePayload, diff: Self::StateDiff) -> Result<()> {
+        *state = diff;
+        Ok(())
+    }
 }
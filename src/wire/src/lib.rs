@@ -1,8 +1,29 @@
+mod chain_id;
+mod codec;
+mod delta;
 pub mod ethereum;
 mod protocol;
 mod state;
 mod update;
+mod upload;
+mod versions;
 
+pub use chain_id::ChainId;
+pub use codec::{BincodeCodec, CodecTag, JsonCodec, WireCodec};
+pub use delta::{state_hash, ChainStateDelta, ChainStateDigest, StateChange};
 pub use protocol::WireProtocol;
 pub use state::{StatePayloadMarshaller, StatePayloadParser};
 pub use update::{UpdatePayloadMarshaller, UpdatePayloadParser};
+pub use upload::{ChunkedUpload, UpdateUpload, UploadSession};
+pub use versions::{ChainWireVersions, SupportedWireVersions, VersionRange};
+
+/// Version of the canister state and update payloads. Version 2 widened chain uids to `ChainId`,
+/// version 3 added codec tags and bincode envelopes.
+pub const WIRE_VERSION: u64 = 3;
+/// Oldest envelope version still parsed.
+pub const MIN_WIRE_VERSION: u64 = 1;
+
+#[derive(serde::Deserialize)]
+struct PayloadVersion {
+    version: u64,
+}
@@ -1,7 +1,31 @@
+use crate::{CodecTag, WireCodec};
+use anyhow::{bail, Result};
 use serde::{de::DeserializeOwned, Serialize};
 
 pub trait WireProtocol {
-    type StatePayload: Serialize + DeserializeOwned + 'static;
+    type StatePayload: Serialize + DeserializeOwned + Clone + Send + 'static;
     type UpdatePayload: Serialize + DeserializeOwned + 'static;
     type Config: Serialize + DeserializeOwned + Clone + 'static;
+    /// Fields of the state changed since an older version, served instead of the full state.
+    type StateDiff: Serialize + DeserializeOwned + 'static;
+    /// Codec of the state, diff and update payloads, the config is always JSON.
+    type Codec: WireCodec<Self::StatePayload> + WireCodec<Self::UpdatePayload> + WireCodec<Self::StateDiff>;
+
+    /// Version of the state and update payload shapes, bumped whenever one of them changes.
+    const PAYLOAD_VERSION: u64 = 1;
+    /// Oldest payload version still parsed, payloads older than `PAYLOAD_VERSION` go through the
+    /// migration hooks below.
+    const MIN_PAYLOAD_VERSION: u64 = 1;
+
+    fn patch_state(state: &mut Self::StatePayload, diff: Self::StateDiff) -> Result<()>;
+
+    /// Decodes a state written with an older payload version into the current shape.
+    fn migrate_state(version: u64, _codec: CodecTag, _data: &[u8]) -> Result<Self::StatePayload> {
+        bail!("No state migration from payload version {}", version)
+    }
+
+    /// Decodes an update written with an older payload version into the current shape.
+    fn migrate_update(version: u64, _codec: CodecTag, _data: &[u8]) -> Result<Self::UpdatePayload> {
+        bail!("No update migration from payload version {}", version)
+    }
 }
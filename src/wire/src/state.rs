@@ -2,18 +2,37 @@ use anyhow::{anyhow, Context, Ok, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::WireProtocol;
+use crate::{
+    codec::encode_payload,
+    versions::{decode_envelope, decode_state},
+    BincodeCodec, ChainId, CodecTag, WireCodec, WireProtocol, WIRE_VERSION,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ChainState {
     pub version: u64,
+    #[serde(default)]
+    pub codec: CodecTag,
     pub state: Vec<u8>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct CanisterState {
     pub version: u64,
-    pub states: HashMap<u16, ChainState>,
+    pub states: HashMap<ChainId, ChainState>,
+}
+
+/// Version 1 payloads keyed chains by `u16` uids.
+#[derive(Serialize, Deserialize)]
+struct CanisterStateV1 {
+    states: HashMap<u16, ChainState>,
+}
+
+impl From<CanisterStateV1> for CanisterState {
+    fn from(state: CanisterStateV1) -> Self {
+        let states = state.states.into_iter().map(|(uid, state)| (uid.into(), state)).collect();
+        Self { version: WIRE_VERSION, states }
+    }
 }
 
 pub struct StatePayloadParser {
@@ -22,40 +41,50 @@ pub struct StatePayloadParser {
 
 impl StatePayloadParser {
     pub fn new(data: Vec<u8>) -> Result<Self> {
-        let state: CanisterState = serde_json::from_slice(data.as_slice()).context("Failed to parse State Payload")?;
-
+        let state = decode_envelope::<CanisterState, CanisterStateV1>(data.as_slice())
+            .context("Failed to parse State Payload")?;
         Ok(Self { state })
     }
 
-    pub fn state<W: WireProtocol>(&self, uid: u16) -> Result<W::StatePayload> {
+    pub fn state<W: WireProtocol>(&self, uid: ChainId) -> Result<W::StatePayload> {
         let raw_state = self.state.states.get(&uid).ok_or(anyhow!("No state for chain uid: {}", uid))?;
 
-        let state = serde_json::from_slice(raw_state.state.as_slice()).context("Failed to parse state.")?;
-
-        Ok(state)
+        decode_state::<W>(raw_state.version, raw_state.codec, raw_state.state.as_slice())
+            .context("Failed to parse state.")
     }
 }
 
 pub struct StatePayloadMarshaller {
     state: CanisterState,
+    json: bool,
 }
 
 impl StatePayloadMarshaller {
     pub fn new() -> Self {
-        Self { state: CanisterState { version: 1, states: HashMap::new() } }
+        Self { state: CanisterState { version: WIRE_VERSION, states: HashMap::new() }, json: false }
     }
 
-    pub fn state<W: WireProtocol>(&mut self, uid: u16, state: W::StatePayload) -> Result<()> {
-        let marshalled_state = serde_json::to_vec(&state).context("Failed to marshal chain state")?;
+    /// Marshals the envelope and every chain state as JSON, for debugging.
+    pub fn json() -> Self {
+        Self { json: true, ..Self::new() }
+    }
+
+    pub fn state<W: WireProtocol>(&mut self, uid: ChainId, state: W::StatePayload) -> Result<()> {
+        let (codec, marshalled_state) =
+            encode_payload::<_, W::Codec>(&state, self.json).context("Failed to marshal chain state")?;
 
         self.state
             .states
-            .insert(uid, ChainState { version: 1, state: marshalled_state });
+            .insert(uid, ChainState { version: W::PAYLOAD_VERSION, codec, state: marshalled_state });
 
         Ok(())
     }
 
     pub fn build(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(&self.state).context("Failed to marshal canister state")
+        if self.json {
+            return serde_json::to_vec(&self.state).context("Failed to marshal canister state");
+        }
+
+        BincodeCodec::encode(&self.state).context("Failed to marshal canister state")
     }
 }
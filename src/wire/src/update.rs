@@ -2,18 +2,46 @@ use anyhow::{Context, Ok, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::WireProtocol;
+use crate::{
+    codec::encode_payload,
+    versions::{decode_envelope, decode_update},
+    BincodeCodec, ChainId, ChunkedUpload, CodecTag, UpdateUpload, WireCodec, WireProtocol, WIRE_VERSION,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ChainUpdates {
     pub version: u64,
+    #[serde(default)]
+    pub codec: CodecTag,
     pub updates: Vec<Vec<u8>>,
+    /// Version and hash of the canister state the updates were computed against, if any.
+    #[serde(default)]
+    pub base_version: Option<u64>,
+    #[serde(default)]
+    pub base_hash: Option<Vec<u8>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct CanisterUpdates {
     pub version: u64,
-    pub updates: HashMap<u16, ChainUpdates>,
+    pub updates: HashMap<ChainId, ChainUpdates>,
+}
+
+/// Version 1 payloads keyed chains by `u16` uids.
+#[derive(Serialize, Deserialize)]
+struct CanisterUpdatesV1 {
+    updates: HashMap<u16, ChainUpdates>,
+}
+
+impl From<CanisterUpdatesV1> for CanisterUpdates {
+    fn from(updates: CanisterUpdatesV1) -> Self {
+        let updates = updates
+            .updates
+            .into_iter()
+            .map(|(uid, updates)| (uid.into(), updates))
+            .collect();
+        Self { version: WIRE_VERSION, updates }
+    }
 }
 
 pub struct UpdatePayloadParser {
@@ -22,52 +50,112 @@ pub struct UpdatePayloadParser {
 
 impl UpdatePayloadParser {
     pub fn new(data: Vec<u8>) -> Result<Self> {
-        let updates: CanisterUpdates =
-            serde_json::from_slice(data.as_slice()).context("Failed to parse Update Payload")?;
-
+        let updates = decode_envelope::<CanisterUpdates, CanisterUpdatesV1>(data.as_slice())
+            .context("Failed to parse Update Payload")?;
         Ok(Self { updates })
     }
 
-    pub fn updates<W: WireProtocol>(&self, uid: u16) -> Result<Vec<W::UpdatePayload>> {
-        let Some(raw_updates) = self.updates.updates.get(&uid) else { return Ok(vec![]) };
+    /// Parses the updates of `uid` individually, so one malformed update doesn't hide the others.
+    pub fn updates<W: WireProtocol>(&self, uid: ChainId) -> Vec<Result<W::UpdatePayload>> {
+        let Some(raw_updates) = self.updates.updates.get(&uid) else { return vec![] };
 
-        let updates = raw_updates
+        raw_updates
             .updates
             .iter()
-            .map(|raw_update| serde_json::from_slice(raw_update.as_slice()).context("Failed to parse update."))
-            .collect::<Result<Vec<W::UpdatePayload>>>()?;
+            .map(|raw_update| {
+                decode_update::<W>(raw_updates.version, raw_updates.codec, raw_update.as_slice())
+                    .context("Failed to parse update.")
+            })
+            .collect()
+    }
+
+    pub fn update_count(&self, uid: ChainId) -> usize {
+        self.updates.updates.get(&uid).map_or(0, |updates| updates.updates.len())
+    }
 
-        Ok(updates)
+    /// Version and hash of the state the updates of `uid` were computed against, when the agent
+    /// sent them.
+    pub fn base(&self, uid: ChainId) -> Option<(u64, &[u8])> {
+        let updates = self.updates.updates.get(&uid)?;
+        Some((updates.base_version?, updates.base_hash.as_deref()?))
+    }
+
+    pub fn chain_uids(&self) -> Vec<ChainId> {
+        self.updates.updates.keys().cloned().collect()
     }
 }
 
 pub struct UpdatePayloadMarshaller {
     updates: CanisterUpdates,
+    json: bool,
+    budget: Option<usize>,
 }
 
 impl UpdatePayloadMarshaller {
     pub fn new() -> Self {
-        Self { updates: CanisterUpdates { version: 1, updates: HashMap::new() } }
+        Self { updates: CanisterUpdates { version: WIRE_VERSION, updates: HashMap::new() }, json: false, budget: None }
+    }
+
+    /// Splits payloads larger than `budget` bytes into chunks of at most `budget` bytes.
+    pub fn with_budget(self, budget: usize) -> Self {
+        Self { budget: Some(budget), ..self }
+    }
+
+    /// Marshals the envelope and every update as JSON, for debugging.
+    pub fn json() -> Self {
+        Self { json: true, ..Self::new() }
     }
 
-    pub fn updates<W: WireProtocol>(&mut self, uid: u16, updates: Vec<W::UpdatePayload>) -> Result<()> {
+    pub fn updates<W: WireProtocol>(&mut self, uid: ChainId, updates: Vec<W::UpdatePayload>) -> Result<()> {
+        let codec = if self.json { CodecTag::Json } else { <W::Codec as WireCodec<W::UpdatePayload>>::TAG };
         let marshalled_updates = updates
-            .into_iter()
-            .map(|update| serde_json::to_vec(&update).context("Failed to marshal chain update"))
+            .iter()
+            .map(|update| {
+                encode_payload::<_, W::Codec>(update, self.json)
+                    .map(|(_, update)| update)
+                    .context("Failed to marshal chain update")
+            })
             .collect::<Result<Vec<Vec<u8>>>>()?;
 
-        self.updates
-            .updates
-            .insert(uid, ChainUpdates { version: 1, updates: marshalled_updates });
+        let chain_updates = self.updates.updates.entry(uid).or_default();
+        chain_updates.version = W::PAYLOAD_VERSION;
+        chain_updates.codec = codec;
+        chain_updates.updates = marshalled_updates;
 
         Ok(())
     }
 
+    /// Records the state the updates of `uid` were computed against, so the canister rejects them
+    /// once that state has changed.
+    pub fn based_on(&mut self, uid: ChainId, version: u64, hash: Vec<u8>) {
+        let chain_updates = self.updates.updates.entry(uid).or_default();
+        chain_updates.base_version = Some(version);
+        chain_updates.base_hash = Some(hash);
+    }
+
+    /// Takes over the chain updates of `other`, replacing those of the same chains.
+    pub fn merge(&mut self, other: Self) {
+        self.updates.updates.extend(other.updates.updates);
+    }
+
     pub fn has_updates(&self) -> bool {
         !self.updates.updates.is_empty()
     }
 
     pub fn build(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(&self.updates).context("Failed to marshal canister update")
+        if self.json {
+            return serde_json::to_vec(&self.updates).context("Failed to marshal canister update");
+        }
+
+        BincodeCodec::encode(&self.updates).context("Failed to marshal canister update")
+    }
+
+    pub fn build_upload(&self) -> Result<UpdateUpload> {
+        let payload = self.build()?;
+
+        Ok(match self.budget {
+            Some(budget) if payload.len() > budget => UpdateUpload::Chunked(ChunkedUpload::new(&payload, budget)),
+            _ => UpdateUpload::Single(payload),
+        })
     }
 }
@@ -0,0 +1,94 @@
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+
+/// Update payload ready to be sent, split into chunks when it exceeds the marshaller's budget.
+pub enum UpdateUpload {
+    Single(Vec<u8>),
+    Chunked(ChunkedUpload),
+}
+
+/// Update payload sent over an upload session: begin with its length and hash, append every
+/// chunk in order, then commit to have the canister apply it.
+pub struct ChunkedUpload {
+    pub total_len: u64,
+    pub sha256: Vec<u8>,
+    pub chunks: Vec<Vec<u8>>,
+}
+
+impl ChunkedUpload {
+    pub fn new(payload: &[u8], chunk_size: usize) -> Self {
+        Self {
+            total_len: payload.len() as u64,
+            sha256: Sha256::digest(payload).to_vec(),
+            chunks: payload.chunks(chunk_size.max(1)).map(|chunk| chunk.to_vec()).collect(),
+        }
+    }
+}
+
+/// Reassembles the chunks of an upload session on the canister.
+pub struct UploadSession {
+    total_len: u64,
+    sha256: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl UploadSession {
+    pub fn new(total_len: u64, sha256: Vec<u8>) -> Result<Self> {
+        if sha256.len() != 32 {
+            bail!("Expected a 32 byte sha256, got {} bytes", sha256.len());
+        }
+
+        Ok(Self { total_len, sha256, data: vec![] })
+    }
+
+    /// Returns the number of bytes received so far.
+    pub fn append(&mut self, chunk: &[u8]) -> Result<u64> {
+        let received = (self.data.len() + chunk.len()) as u64;
+        if received > self.total_len {
+            bail!("Upload exceeds its announced length of {} bytes", self.total_len);
+        }
+
+        self.data.extend_from_slice(chunk);
+        Ok(received)
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>> {
+        if self.data.len() as u64 != self.total_len {
+            bail!("Upload is incomplete, received {} of {} bytes", self.data.len(), self.total_len);
+        }
+        if Sha256::digest(&self.data).as_slice() != self.sha256.as_slice() {
+            bail!("Upload does not match its announced sha256");
+        }
+
+        Ok(self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkedUpload, UploadSession};
+
+    #[test]
+    fn test_upload_session() {
+        let payload: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let upload = ChunkedUpload::new(&payload, 300);
+        assert_eq!(upload.chunks.len(), 4);
+
+        let mut session = UploadSession::new(upload.total_len, upload.sha256.clone()).unwrap();
+        for chunk in &upload.chunks {
+            session.append(chunk).unwrap();
+        }
+        assert!(session.append(&[0]).is_err());
+        assert_eq!(session.finish().unwrap(), payload);
+
+        let mut session = UploadSession::new(upload.total_len, upload.sha256.clone()).unwrap();
+        session.append(&upload.chunks[0]).unwrap();
+        assert!(session.finish().is_err());
+
+        let mut session = UploadSession::new(upload.total_len, upload.sha256).unwrap();
+        for chunk in upload.chunks.iter().rev() {
+            session.append(chunk).unwrap();
+        }
+        assert!(session.finish().is_err());
+    }
+}
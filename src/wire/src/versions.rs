@@ -0,0 +1,219 @@
+use crate::{
+    codec::{decode_payload, is_json_envelope},
+    BincodeCodec, ChainId, CodecTag, JsonCodec, PayloadVersion, WireCodec, WireProtocol, MIN_WIRE_VERSION,
+    WIRE_VERSION,
+};
+use anyhow::{bail, Context, Result};
+use candid::CandidType;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl VersionRange {
+    /// Envelope versions parsed by this build.
+    pub fn envelope() -> Self {
+        Self { min: MIN_WIRE_VERSION, max: WIRE_VERSION }
+    }
+
+    /// Chain payload versions of `W` parsed by this build.
+    pub fn payload<W: WireProtocol>() -> Self {
+        Self { min: W::MIN_PAYLOAD_VERSION, max: W::PAYLOAD_VERSION }
+    }
+
+    pub fn contains(&self, version: u64) -> bool {
+        (self.min..=self.max).contains(&version)
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ChainWireVersions {
+    pub chain: ChainId,
+    pub payload: VersionRange,
+}
+
+/// Wire versions a canister parses, for agents to check that it accepts what they write before
+/// sending anything.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SupportedWireVersions {
+    pub envelope: VersionRange,
+    pub chains: Vec<ChainWireVersions>,
+}
+
+impl SupportedWireVersions {
+    /// Fails unless the envelope and `uid` payload versions written by this build are supported.
+    /// During a rolling upgrade the canister goes first, as it migrates older payloads but agents
+    /// can't write them.
+    pub fn ensure_supported<W: WireProtocol>(&self, uid: ChainId) -> Result<()> {
+        if !self.envelope.contains(WIRE_VERSION) {
+            bail!("Canister doesn't support wire version {}, supports {:?}", WIRE_VERSION, self.envelope);
+        }
+
+        let Some(chain) = self.chains.iter().find(|chain| chain.chain == uid) else {
+            bail!("Canister reports no wire versions for chain {}", uid);
+        };
+        if !chain.payload.contains(W::PAYLOAD_VERSION) {
+            bail!(
+                "Canister doesn't support payload version {} of chain {}, supports {:?}",
+                W::PAYLOAD_VERSION,
+                uid,
+                chain.payload
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn ensure_payload_version<W: WireProtocol>(version: u64, kind: &str) -> Result<()> {
+    let supported = VersionRange::payload::<W>();
+    if !supported.contains(version) {
+        bail!("Unsupported {} payload version: {}, supports {:?}", kind, version, supported);
+    }
+
+    Ok(())
+}
+
+/// Decodes a JSON or bincode envelope of any supported version, version 1 envelopes are read as
+/// `V1`. Bincode envelopes start with the varint encoded version, which `V1` doesn't carry.
+pub(crate) fn decode_envelope<T, V1>(data: &[u8]) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    V1: Serialize + DeserializeOwned + Into<T>,
+{
+    let json = is_json_envelope(data);
+    let (version, read) = if json {
+        let PayloadVersion { version } = serde_json::from_slice(data).context("Failed to decode JSON payload")?;
+        (version, 0)
+    } else {
+        bincode::serde::decode_from_slice::<u64, _>(data, bincode::config::standard())
+            .context("Failed to decode bincode payload")?
+    };
+
+    let supported = VersionRange::envelope();
+    if !supported.contains(version) {
+        bail!("Unsupported envelope version: {}, supports {:?}", version, supported);
+    }
+
+    match (version, json) {
+        (1, true) => Ok(<JsonCodec as WireCodec<V1>>::decode(data)?.into()),
+        (1, false) => Ok(<BincodeCodec as WireCodec<V1>>::decode(&data[read..])?.into()),
+        (_, true) => JsonCodec::decode(data),
+        (_, false) => BincodeCodec::decode(data),
+    }
+}
+
+/// Decodes a state written with payload `version`, migrating it when older than the current one.
+pub(crate) fn decode_state<W: WireProtocol>(version: u64, codec: CodecTag, data: &[u8]) -> Result<W::StatePayload> {
+    ensure_payload_version::<W>(version, "state")?;
+    if version == W::PAYLOAD_VERSION {
+        return decode_payload::<_, W::Codec>(codec, data);
+    }
+
+    W::migrate_state(version, codec, data)
+}
+
+/// Decodes an update written with payload `version`, migrating it when older than the current one.
+pub(crate) fn decode_update<W: WireProtocol>(version: u64, codec: CodecTag, data: &[u8]) -> Result<W::UpdatePayload> {
+    ensure_payload_version::<W>(version, "update")?;
+    if version == W::PAYLOAD_VERSION {
+        return decode_payload::<_, W::Codec>(codec, data);
+    }
+
+    W::migrate_update(version, codec, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BincodeCodec, JsonCodec, WireCodec};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Update {
+        value: u64,
+    }
+
+    /// Version 1 updates carried the value as a string.
+    #[derive(Deserialize)]
+    struct UpdateV1 {
+        value: String,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Envelope {
+        version: u64,
+        value: u64,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct EnvelopeV1 {
+        value: String,
+    }
+
+    impl From<EnvelopeV1> for Envelope {
+        fn from(envelope: EnvelopeV1) -> Self {
+            Self { version: WIRE_VERSION, value: envelope.value.parse().unwrap() }
+        }
+    }
+
+    struct TestProtocol;
+
+    impl WireProtocol for TestProtocol {
+        type StatePayload = Update;
+        type UpdatePayload = Update;
+        type Config = ();
+        type StateDiff = Update;
+        type Codec = JsonCodec;
+
+        const PAYLOAD_VERSION: u64 = 2;
+
+        fn patch_state(state: &mut Update, diff: Update) -> Result<()> {
+            *state = diff;
+            Ok(())
+        }
+
+        fn migrate_update(_: u64, _: CodecTag, data: &[u8]) -> Result<Update> {
+            let update: UpdateV1 = serde_json::from_slice(data)?;
+            Ok(Update { value: update.value.parse()? })
+        }
+    }
+
+    #[test]
+    fn test_payload_versions() {
+        let current = JsonCodec::encode(&Update { value: 7 }).unwrap();
+        let old = br#"{"value":"7"}"#;
+
+        assert_eq!(decode_update::<TestProtocol>(2, CodecTag::Json, &current).unwrap(), Update { value: 7 });
+        assert_eq!(decode_update::<TestProtocol>(1, CodecTag::Json, old).unwrap(), Update { value: 7 });
+        assert!(decode_update::<TestProtocol>(3, CodecTag::Json, &current).is_err());
+        assert!(decode_state::<TestProtocol>(1, CodecTag::Json, old).is_err());
+
+        let supported = SupportedWireVersions {
+            envelope: VersionRange::envelope(),
+            chains: vec![ChainWireVersions { chain: ChainId::default(), payload: VersionRange { min: 1, max: 1 } }],
+        };
+        assert!(supported.ensure_supported::<TestProtocol>(ChainId::default()).is_err());
+    }
+
+    #[test]
+    fn test_envelope_versions() {
+        let decode = |data: &[u8]| decode_envelope::<Envelope, EnvelopeV1>(data).map(|envelope| envelope.value);
+        let current = Envelope { version: WIRE_VERSION, value: 7 };
+        let v1 = (1u64, EnvelopeV1 { value: "7".into() });
+
+        assert_eq!(decode(&JsonCodec::encode(&current).unwrap()).unwrap(), 7);
+        assert_eq!(decode(&BincodeCodec::encode(&current).unwrap()).unwrap(), 7);
+        assert_eq!(decode(br#"{"version":1,"value":"7"}"#).unwrap(), 7);
+        assert_eq!(decode(&BincodeCodec::encode(&v1).unwrap()).unwrap(), 7);
+
+        for version in [0, WIRE_VERSION + 1] {
+            let envelope = Envelope { version, value: 7 };
+            assert!(decode(&JsonCodec::encode(&envelope).unwrap()).is_err());
+            assert!(decode(&BincodeCodec::encode(&envelope).unwrap()).is_err());
+        }
+    }
+}